@@ -0,0 +1,204 @@
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::str::FromStr;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use crate::canvas::CanvasSource;
+use crate::capture::CommandCanvas;
+
+/// Terminal previewer for tachyonfx effects.
+#[derive(Debug, Parser)]
+#[command(name = "tfxed", version, about, args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// ANSI-escaped text used as the canvas, a widget layout (`.ron`), or
+    /// `preset:NAME` for a built-in canvas; `-` or piped input reads it from stdin
+    #[arg(value_name = "CANVAS")]
+    canvas: Option<CanvasSource>,
+
+    /// canvas; alternative to the positional argument
+    #[arg(long = "canvas", value_name = "CANVAS", conflicts_with = "canvas")]
+    canvas_opt: Option<CanvasSource>,
+
+    /// run the command in a pseudo-terminal and use its screen as the canvas
+    #[arg(long, value_name = "COMMAND", conflicts_with_all = ["canvas", "canvas_opt"])]
+    canvas_cmd: Option<String>,
+
+    /// size of the pseudo-terminal for --canvas-cmd
+    #[arg(long, value_name = "COLSxROWS", value_parser = parse_size::<u16>, default_value = "80x24",
+        requires = "canvas_cmd", conflicts_with_all = ["canvas", "canvas_opt"])]
+    canvas_size: (u16, u16),
+
+    /// how long to wait for --canvas-cmd to exit before capturing its screen, in
+    /// milliseconds; commands still running are stopped
+    #[arg(long, value_name = "MS", default_value_t = 1000,
+        requires = "canvas_cmd", conflicts_with_all = ["canvas", "canvas_opt"])]
+    canvas_cmd_wait: u64,
+
+    /// effect DSL file; recompiled whenever it changes on disk
+    #[arg(value_name = "DSL")]
+    dsl_file: Option<PathBuf>,
+
+    /// effect DSL file; alternative to the positional argument
+    #[arg(long = "dsl", value_name = "FILE", conflicts_with = "dsl_file")]
+    dsl_opt: Option<PathBuf>,
+
+    /// re-run the effect when it finishes, optionally pausing between runs
+    #[arg(long = "loop", value_name = "DELAY_MS", num_args = 0..=1, default_missing_value = "0")]
+    pub loop_delay: Option<u32>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Render the effect without a terminal, writing each frame to disk
+    Render(RenderArgs),
+    /// Compare effects against their golden snapshots
+    Test(TestArgs),
+    /// Run a language server for the effect DSL over stdin and stdout
+    Lsp,
+    /// List the effects, methods and values known to the DSL
+    Completions(CompletionsArgs),
+    /// Reformat effect DSL files in place
+    Fmt(FmtArgs),
+}
+
+#[derive(Debug, clap::Args)]
+pub struct RenderArgs {
+    /// ANSI-escaped text used as the canvas, a widget layout (`.ron`), or
+    /// `preset:NAME` for a built-in canvas; `-` reads it from stdin
+    pub canvas: CanvasSource,
+
+    /// effect DSL file
+    pub dsl: PathBuf,
+
+    /// output format
+    #[arg(short, long, value_enum, default_value_t = RenderFormat::Ansi)]
+    pub format: RenderFormat,
+
+    /// output file, or directory for the `ansi` format; defaults to `frames`
+    /// or `effect.<format>`
+    #[arg(short, long, value_name = "PATH")]
+    pub out: Option<PathBuf>,
+
+    /// time between frames, in milliseconds
+    #[arg(long, value_name = "MS", default_value_t = 16)]
+    pub frame_ms: u32,
+
+    /// frames per second; alternative to --frame-ms
+    #[arg(long, conflicts_with = "frame_ms")]
+    pub fps: Option<u32>,
+
+    /// size of a cell in pixels, for the `gif` and `apng` formats
+    #[arg(long, value_name = "WxH", value_parser = parse_size::<u32>, default_value = "9x18")]
+    pub cell_size: (u32, u32),
+
+    /// how long to render, in milliseconds; defaults to the duration of the effect
+    #[arg(long, value_name = "MS")]
+    pub duration: Option<u32>,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct TestArgs {
+    /// directory of cases, one subdirectory each holding `canvas.ansi`, `effect.dsl`
+    /// and `timestamps`
+    #[arg(default_value = "snapshots")]
+    pub dir: PathBuf,
+
+    /// write the rendered frames as the new snapshots instead of comparing
+    #[arg(long)]
+    pub accept: bool,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct CompletionsArgs {
+    /// print the catalogue as JSON, with signatures, docs and the text to insert
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct FmtArgs {
+    /// effect DSL files; `-` or none formats stdin to stdout
+    pub files: Vec<PathBuf>,
+
+    /// list the files that aren't formatted instead of rewriting them; fails if any
+    #[arg(long)]
+    pub check: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RenderFormat {
+    /// one ANSI-escaped text file per frame
+    Ansi,
+    /// asciinema recording (asciicast v2)
+    #[value(name = "cast")]
+    Asciicast,
+    /// animated SVG
+    Svg,
+    /// animated GIF
+    Gif,
+    /// animated PNG
+    Apng,
+    /// every cell of every frame, one JSON object per line
+    #[value(name = "jsonl")]
+    JsonLines,
+}
+
+impl RenderArgs {
+    pub fn out(&self) -> PathBuf {
+        let default = match self.format {
+            RenderFormat::Ansi      => "frames",
+            RenderFormat::Asciicast => "effect.cast",
+            RenderFormat::Svg       => "effect.svg",
+            RenderFormat::Gif       => "effect.gif",
+            RenderFormat::Apng      => "effect.png",
+            RenderFormat::JsonLines => "effect.jsonl",
+        };
+
+        self.out.clone().unwrap_or_else(|| default.into())
+    }
+
+    /// time between frames, in milliseconds
+    pub fn frame_ms(&self) -> u32 {
+        match self.fps {
+            Some(fps) => (1000 + fps / 2) / fps.max(1),
+            None      => self.frame_ms,
+        }.max(1)
+    }
+}
+
+fn parse_size<T: FromStr + Default + PartialOrd>(s: &str) -> Result<(T, T), String> {
+    let invalid = || format!("expected WIDTHxHEIGHT; got '{s}'");
+    let (width, height) = s.split_once('x').ok_or_else(invalid)?;
+    match (width.parse(), height.parse()) {
+        (Ok(width), Ok(height)) if width > T::default() && height > T::default() => Ok((width, height)),
+        _ => Err(invalid()),
+    }
+}
+
+impl Args {
+    pub fn canvas(&self) -> CanvasSource {
+        if let Some(command) = &self.canvas_cmd {
+            return CanvasSource::Command(CommandCanvas {
+                command: command.clone(),
+                size: self.canvas_size,
+                wait: std::time::Duration::from_millis(self.canvas_cmd_wait),
+            });
+        }
+
+        match self.canvas.as_ref().or(self.canvas_opt.as_ref()) {
+            Some(canvas)                            => canvas.clone(),
+            None if !std::io::stdin().is_terminal() => CanvasSource::Stdin,
+            None => Args::command()
+                .error(ErrorKind::MissingRequiredArgument, "a canvas is required; pass a file, \
+                    --canvas, --canvas-cmd or pipe the canvas to stdin")
+                .exit(),
+        }
+    }
+
+    pub fn dsl(&self) -> Option<&PathBuf> {
+        self.dsl_file.as_ref().or(self.dsl_opt.as_ref())
+    }
+}
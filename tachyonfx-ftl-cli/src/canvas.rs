@@ -0,0 +1,98 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use color_eyre::eyre::{Result, WrapErr};
+use tfxed_core::AppEvent;
+use crate::capture::CommandCanvas;
+use tfxed_core::canvas::presets::{self, Preset, PRESETS};
+
+const PRESET_PREFIX: &str = "preset:";
+const STDIN: &str = "-";
+const LAYOUT_EXTENSION: &str = "ron";
+
+/// Where the canvas comes from: an ANSI-escaped text file or `-` for standard
+/// input, a widget layout (`.ron`), `preset:NAME` for one of the canvases
+/// bundled with tfxed-core, or the screen of a command.
+#[derive(Clone)]
+pub enum CanvasSource {
+    File(PathBuf),
+    Stdin,
+    Layout(PathBuf),
+    Preset(&'static Preset),
+    Command(CommandCanvas),
+}
+
+impl CanvasSource {
+    /// the ANSI-escaped text of the canvas, or the layout for widget layouts
+    pub fn read(&self) -> Result<String> {
+        match self {
+            CanvasSource::File(path) | CanvasSource::Layout(path) => std::fs::read_to_string(path)
+                .wrap_err_with(|| format!("failed to read canvas {}", path.display())),
+            CanvasSource::Stdin                                   => std::io::read_to_string(std::io::stdin())
+                .wrap_err("failed to read canvas from stdin"),
+            CanvasSource::Preset(preset)                          => Ok(preset.source()),
+            CanvasSource::Command(command)                        => command.capture(),
+        }
+    }
+
+    /// the file to watch for changes, if any
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            CanvasSource::File(path) | CanvasSource::Layout(path) => Some(path),
+            _                                                     => None,
+        }
+    }
+
+    /// the event replacing the app's canvas with the text returned by [Self::read]
+    pub fn event(&self) -> fn(String) -> AppEvent {
+        match self {
+            CanvasSource::Layout(_) => AppEvent::UpdateCanvasLayout,
+            _                       => AppEvent::UpdateCanvas,
+        }
+    }
+}
+
+impl FromStr for CanvasSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == STDIN {
+            return Ok(CanvasSource::Stdin);
+        }
+
+        let Some(name) = s.strip_prefix(PRESET_PREFIX) else {
+            let path = PathBuf::from(s);
+            return match path.extension().is_some_and(|ext| ext == LAYOUT_EXTENSION) {
+                true  => Ok(CanvasSource::Layout(path)),
+                false => Ok(CanvasSource::File(path)),
+            };
+        };
+
+        presets::find(name)
+            .map(CanvasSource::Preset)
+            .ok_or_else(|| {
+                let available = PRESETS.iter()
+                    .map(|p| p.name)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("unknown preset '{name}'; available presets: {available}")
+            })
+    }
+}
+
+impl fmt::Display for CanvasSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CanvasSource::File(path) | CanvasSource::Layout(path) => write!(f, "{}", path.display()),
+            CanvasSource::Stdin                                   => f.write_str("stdin"),
+            CanvasSource::Preset(preset)                          => write!(f, "{PRESET_PREFIX}{}", preset.name),
+            CanvasSource::Command(command)                        => write!(f, "'{}'", command.command),
+        }
+    }
+}
+
+impl fmt::Debug for CanvasSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
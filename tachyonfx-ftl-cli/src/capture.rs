@@ -0,0 +1,214 @@
+use std::io::Read;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use portable_pty::{native_pty_system, CommandBuilder, PtySize};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use tfxed_core::canvas::buffer_to_ansi;
+
+/// Output still arriving after the command has exited is collected until the
+/// pseudo-terminal has been quiet for this long.
+const DRAIN_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// A command whose screen is used as the canvas.
+#[derive(Debug, Clone)]
+pub struct CommandCanvas {
+    pub command: String,
+    /// columns and rows of the pseudo-terminal
+    pub size: (u16, u16),
+    /// how long to wait for the command to exit
+    pub wait: Duration,
+}
+
+impl CommandCanvas {
+    /// Runs the command through the shell in a pseudo-terminal and returns its
+    /// screen as ANSI-escaped text, once the command exits or after `wait`,
+    /// whichever comes first. A command still running by then is killed.
+    pub fn capture(&self) -> Result<String> {
+        let (cols, rows) = self.size;
+        let pty = native_pty_system()
+            .openpty(PtySize { rows, cols, pixel_width: 0, pixel_height: 0 })
+            .map_err(|e| eyre!("{e}"))
+            .wrap_err("failed to open a pseudo-terminal")?;
+
+        let mut child = pty.slave.spawn_command(self.shell_command())
+            .map_err(|e| eyre!("{e}"))
+            .wrap_err_with(|| format!("failed to run '{}'", self.command))?;
+        // only the child holds the slave side open, so reads end when it exits
+        drop(pty.slave);
+
+        let output = read_in_background(pty.master.try_clone_reader().map_err(|e| eyre!("{e}"))?);
+        let mut parser = vt100::Parser::new(rows, cols, 0);
+
+        let deadline = Instant::now() + self.wait;
+        while child.try_wait()?.is_none() && Instant::now() < deadline {
+            let timeout = deadline.saturating_duration_since(Instant::now()).min(DRAIN_TIMEOUT);
+            match output.recv_timeout(timeout) {
+                Ok(bytes) => parser.process(&bytes),
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        if child.try_wait()?.is_none() {
+            // captured before stopping the command, as many TUIs clear or restore
+            // the screen on their way out
+            output.try_iter().for_each(|bytes| parser.process(&bytes));
+            child.kill().wrap_err_with(|| format!("failed to stop '{}'", self.command))?;
+        } else {
+            // bounded, in case the command left a process behind that keeps writing
+            let drain_deadline = Instant::now() + 10 * DRAIN_TIMEOUT;
+            while let Ok(bytes) = output.recv_timeout(DRAIN_TIMEOUT) {
+                parser.process(&bytes);
+                if Instant::now() >= drain_deadline {
+                    break;
+                }
+            }
+        }
+
+        Ok(buffer_to_ansi(&screen_buffer(parser.screen())))
+    }
+
+    fn shell_command(&self) -> CommandBuilder {
+        #[cfg(windows)]
+        let mut cmd = {
+            let mut cmd = CommandBuilder::new("cmd");
+            cmd.args(["/C", &self.command]);
+            cmd
+        };
+        #[cfg(not(windows))]
+        let mut cmd = {
+            let mut cmd = CommandBuilder::new("sh");
+            cmd.args(["-c", &self.command]);
+            cmd
+        };
+
+        if let Ok(cwd) = std::env::current_dir() {
+            cmd.cwd(cwd);
+        }
+        cmd.env("TERM", "xterm-256color");
+        cmd.env("COLORTERM", "truecolor");
+        cmd
+    }
+}
+
+/// forwards everything read from `reader` to the returned channel
+fn read_in_background(mut reader: Box<dyn Read + Send>) -> mpsc::Receiver<Vec<u8>> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut buf = [0; 4096];
+        while let Ok(n @ 1..) = reader.read(&mut buf) {
+            if sender.send(buf[..n].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+
+    receiver
+}
+
+fn screen_buffer(screen: &vt100::Screen) -> Buffer {
+    let (rows, cols) = screen.size();
+    let mut buf = Buffer::empty(Rect::new(0, 0, cols, rows));
+
+    for (y, x) in (0..rows).flat_map(|y| (0..cols).map(move |x| (y, x))) {
+        let Some(cell) = screen.cell(y, x) else { continue };
+        if cell.is_wide_continuation() {
+            continue;
+        }
+
+        let symbol = match cell.has_contents() {
+            true  => cell.contents(),
+            false => " ".to_string(),
+        };
+
+        let modifiers = [
+            (cell.bold(), Modifier::BOLD),
+            (cell.italic(), Modifier::ITALIC),
+            (cell.underline(), Modifier::UNDERLINED),
+            (cell.inverse(), Modifier::REVERSED),
+        ].into_iter()
+            .filter(|(enabled, _)| *enabled)
+            .fold(Modifier::empty(), |acc, (_, m)| acc | m);
+
+        let style = Style::new()
+            .fg(color(cell.fgcolor()))
+            .bg(color(cell.bgcolor()))
+            .add_modifier(modifiers);
+
+        buf[(x, y)].set_symbol(&symbol).set_style(style);
+    }
+
+    buf
+}
+
+fn color(color: vt100::Color) -> Color {
+    match color {
+        vt100::Color::Default       => Color::Reset,
+        vt100::Color::Idx(i @ ..16) => ANSI_COLORS[i as usize],
+        vt100::Color::Idx(i)        => Color::Indexed(i),
+        vt100::Color::Rgb(r, g, b)  => Color::Rgb(r, g, b),
+    }
+}
+
+const ANSI_COLORS: [Color; 16] = [
+    Color::Black, Color::Red, Color::Green, Color::Yellow,
+    Color::Blue, Color::Magenta, Color::Cyan, Color::Gray,
+    Color::DarkGray, Color::LightRed, Color::LightGreen, Color::LightYellow,
+    Color::LightBlue, Color::LightMagenta, Color::LightCyan, Color::White,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(rows: u16, cols: u16, output: &str) -> Buffer {
+        let mut parser = vt100::Parser::new(rows, cols, 0);
+        parser.process(output.as_bytes());
+        screen_buffer(parser.screen())
+    }
+
+    fn symbols(buf: &Buffer) -> Vec<&str> {
+        buf.content().iter().map(|c| c.symbol()).collect()
+    }
+
+    #[test]
+    fn converts_colors_and_modifiers() {
+        let buf = screen(1, 6, "\x1b[1;31ma\x1b[0;3;4;7;38;5;208;48;5;9mb\x1b[0;38;2;1;2;3;44mc\x1b[0md");
+
+        let styles: Vec<_> = buf.content()[..4].iter().map(|c| (c.symbol(), c.fg, c.bg, c.modifier)).collect();
+        assert_eq!(styles, [
+            ("a", Color::Red, Color::Reset, Modifier::BOLD),
+            ("b", Color::Indexed(208), Color::LightRed, Modifier::ITALIC | Modifier::UNDERLINED | Modifier::REVERSED),
+            ("c", Color::Rgb(1, 2, 3), Color::Blue, Modifier::empty()),
+            ("d", Color::Reset, Color::Reset, Modifier::empty()),
+        ]);
+    }
+
+    #[test]
+    fn fills_the_screen_size_with_blanks() {
+        let buf = screen(2, 3, "ab\r\n\x1b[2Cc");
+
+        assert_eq!(buf.area, Rect::new(0, 0, 3, 2));
+        assert_eq!(symbols(&buf).concat(), "ab   c");
+    }
+
+    #[test]
+    fn wide_characters_keep_their_width() {
+        let buf = screen(1, 4, "世b");
+
+        assert_eq!(symbols(&buf), ["世", " ", "b", " "]);
+        assert_eq!(buffer_to_ansi(&buf), "世b ");
+    }
+
+    #[test]
+    fn maps_the_16_ansi_colors() {
+        assert_eq!(color(vt100::Color::Default), Color::Reset);
+        assert_eq!(color(vt100::Color::Idx(0)), Color::Black);
+        assert_eq!(color(vt100::Color::Idx(8)), Color::DarkGray);
+        assert_eq!(color(vt100::Color::Idx(15)), Color::White);
+        assert_eq!(color(vt100::Color::Idx(16)), Color::Indexed(16));
+    }
+}
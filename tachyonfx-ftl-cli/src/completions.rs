@@ -0,0 +1,18 @@
+use color_eyre::eyre::Result;
+use tfxed_core::catalog::{self, SYMBOLS};
+use crate::args::CompletionsArgs;
+
+/// Prints the DSL catalogue: as JSON for editors, or one signature per line
+/// followed by its description.
+pub fn print(args: &CompletionsArgs) -> Result<()> {
+    if args.json {
+        println!("{}", catalog::catalog_json());
+        return Ok(());
+    }
+
+    for symbol in SYMBOLS {
+        println!("{symbol}\n    {}", symbol.doc);
+    }
+
+    Ok(())
+}
@@ -1,6 +1,8 @@
 use std::sync::mpsc;
 use crossterm::event::KeyEvent as CrosstermKeyEvent;
 use tfxed_core::{AppEvent, Dispatcher, KeyCode, KeyEvent, ModifierKeys};
+use tfxed_core::event::ERROR_POPUP_PAGE;
+use crate::file_watcher::WatchedFile;
 
 #[derive(Debug)]
 pub struct EventHandler {
@@ -20,21 +22,13 @@ impl EventHandler {
     }
 
     pub fn try_next(&self) -> Option<AppEvent> {
-        match self.receiver.try_recv() {
-            Ok(e)  => Some(e),
-            Err(_) => None
-        }
+        self.receiver.try_recv().ok()
     }
 
-    /// iterates over all currently available events
-    pub fn receive_events<F>(&self, mut f: F)
-        where F: FnMut(AppEvent)
-    {
-        // f(self.next().unwrap());
-        while let Some(event) = self.try_next() { f(event) }
-    }
-
-    pub fn new(tick_rate: std::time::Duration) -> Self {
+    pub fn new(
+        tick_rate: std::time::Duration,
+        mut watched_files: Vec<WatchedFile>,
+    ) -> Self {
         use ratatui::crossterm::event;
 
         let (sender, receiver) = mpsc::channel();
@@ -48,11 +42,17 @@ impl EventHandler {
                         .checked_sub(last_tick.elapsed())
                         .unwrap_or(tick_rate);
 
+                    // crossterm reads from /dev/tty when stdin isn't a terminal,
+                    // so keys still arrive when the canvas is piped to stdin
                     if event::poll(timeout).expect("successfully polled for events") {
                         Self::consume_event(&sender);
                     }
 
                     if last_tick.elapsed() >= tick_rate {
+                        watched_files.iter_mut()
+                            .filter_map(WatchedFile::poll)
+                            .for_each(|e| sender.dispatch(e));
+
                         sender.dispatch(AppEvent::Tick);
                         last_tick = std::time::Instant::now();
                     }
@@ -69,7 +69,8 @@ impl EventHandler {
 
         match crossterm::event::read().expect("event is read") {
             CrosstermEvent::Key(e) if e.kind == KeyEventKind::Press =>
-                sender.send(AppEvent::KeyPress(convert_key_event(e))),
+                sender.send(key_binding(&e)
+                    .unwrap_or_else(|| AppEvent::KeyPress(convert_key_event(e)))),
             CrosstermEvent::Resize(w, h) =>
                 sender.send(AppEvent::Resize(w, h)),
 
@@ -78,6 +79,39 @@ impl EventHandler {
     }
 }
 
+/// key bindings for controlling playback of the editor effect and the panels
+fn key_binding(e: &CrosstermKeyEvent) -> Option<AppEvent> {
+    use crossterm::event::{KeyCode as CtKeyCode, KeyModifiers};
+    use tachyonfx::Duration;
+
+    let seek_step = match e.modifiers.contains(KeyModifiers::SHIFT) {
+        true  => Duration::from_millis(1000),
+        false => Duration::from_millis(100),
+    };
+
+    match e.code {
+        CtKeyCode::Left      => Some(AppEvent::SeekBackward(seek_step)),
+        CtKeyCode::Right     => Some(AppEvent::SeekForward(seek_step)),
+        CtKeyCode::Char(' ') => Some(AppEvent::TogglePause),
+        CtKeyCode::Char('r') => Some(AppEvent::Restart),
+        CtKeyCode::Char('.') => Some(AppEvent::StepForward),
+        CtKeyCode::Char(',') => Some(AppEvent::StepBackward),
+        CtKeyCode::Char('+') => Some(AppEvent::IncreaseTimeScale),
+        CtKeyCode::Char('=') => Some(AppEvent::IncreaseTimeScale),
+        CtKeyCode::Char('-') => Some(AppEvent::DecreaseTimeScale),
+        CtKeyCode::Char('0') => Some(AppEvent::SetTimeScale(1.0)),
+        CtKeyCode::Char('s') => Some(AppEvent::ToggleSlowMotion),
+        CtKeyCode::Char('l') => Some(AppEvent::ToggleLoop),
+        CtKeyCode::Char('t') => Some(AppEvent::ToggleTimeline),
+        CtKeyCode::Char('i') => Some(AppEvent::ToggleInspector),
+        CtKeyCode::Up        => Some(AppEvent::ScrollErrorPopup(-1)),
+        CtKeyCode::Down      => Some(AppEvent::ScrollErrorPopup(1)),
+        CtKeyCode::PageUp    => Some(AppEvent::ScrollErrorPopup(-ERROR_POPUP_PAGE)),
+        CtKeyCode::PageDown  => Some(AppEvent::ScrollErrorPopup(ERROR_POPUP_PAGE)),
+        CtKeyCode::Char('x') => Some(AppEvent::DismissErrorPopup),
+        _                    => None,
+    }
+}
 
 fn convert_key_event(CrosstermKeyEvent { code, modifiers, .. }: CrosstermKeyEvent) -> KeyEvent {
     use crossterm::event::{KeyCode as CtKeyCode, KeyModifiers};
@@ -0,0 +1,125 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tfxed_core::AppEvent;
+
+/// A file which is re-read whenever its modification time changes.
+///
+/// Polled from the [EventHandler](crate::event_handler::EventHandler) thread; each
+/// change is turned into an [AppEvent] carrying the new file contents.
+#[derive(Debug)]
+pub struct WatchedFile {
+    path: PathBuf,
+    last_modified: Option<SystemTime>,
+    /// modification time of an empty read held back until the next poll
+    empty_since: Option<SystemTime>,
+    into_event: fn(String) -> AppEvent,
+}
+
+impl WatchedFile {
+    pub fn new(path: impl Into<PathBuf>, into_event: fn(String) -> AppEvent) -> Self {
+        let path = path.into();
+        let last_modified = modified(&path);
+
+        Self { path, last_modified, empty_since: None, into_event }
+    }
+
+    /// returns an event with the file contents if the file changed since the last poll
+    pub fn poll(&mut self) -> Option<AppEvent> {
+        let modified = modified(&self.path);
+        if modified.is_none() || modified == self.last_modified {
+            return None;
+        }
+
+        let contents = std::fs::read_to_string(&self.path).ok()?;
+
+        // editors may truncate the file before writing it; an empty file is only
+        // reloaded if it's still unchanged on the next poll
+        if contents.is_empty() && self.empty_since != modified {
+            self.empty_since = modified;
+            return None;
+        }
+
+        self.empty_since = None;
+        self.last_modified = modified;
+        Some((self.into_event)(contents))
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::time::Duration;
+    use super::*;
+
+    /// a file under the system temp dir, removed when dropped
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("tfxed-watch-{}-{name}", std::process::id()));
+            std::fs::write(&path, "initial").unwrap();
+            Self(path)
+        }
+
+        /// writes `contents` with a modification time `secs` after the epoch,
+        /// as writes within a test may share the same timestamp otherwise
+        fn write(&self, contents: &str, secs: u64) {
+            std::fs::write(&self.0, contents).unwrap();
+            File::options().write(true).open(&self.0).unwrap()
+                .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+                .unwrap();
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn contents(event: Option<AppEvent>) -> Option<String> {
+        match event? {
+            AppEvent::UpdateCanvas(contents) => Some(contents),
+            _                                => None,
+        }
+    }
+
+    #[test]
+    fn reloads_the_file_when_it_changes() {
+        let file = TempFile::new("changes");
+        let mut watched = WatchedFile::new(&file.0, AppEvent::UpdateCanvas);
+        assert_eq!(contents(watched.poll()), None);
+
+        file.write("hello", 1);
+        assert_eq!(contents(watched.poll()), Some("hello".into()));
+        assert_eq!(contents(watched.poll()), None);
+    }
+
+    #[test]
+    fn waits_a_poll_for_the_write_after_a_truncate() {
+        let file = TempFile::new("truncate");
+        let mut watched = WatchedFile::new(&file.0, AppEvent::UpdateCanvas);
+
+        file.write("", 1);
+        assert_eq!(contents(watched.poll()), None);
+        file.write("hello", 2);
+        assert_eq!(contents(watched.poll()), Some("hello".into()));
+    }
+
+    #[test]
+    fn reloads_a_file_left_empty() {
+        let file = TempFile::new("empty");
+        let mut watched = WatchedFile::new(&file.0, AppEvent::UpdateCanvas);
+
+        file.write("", 1);
+        assert_eq!(contents(watched.poll()), None);
+        assert_eq!(contents(watched.poll()), Some(String::new()));
+        assert_eq!(contents(watched.poll()), None);
+    }
+}
@@ -0,0 +1,59 @@
+use std::io::{Read, Write};
+use std::path::Path;
+use color_eyre::eyre::{bail, eyre, Result, WrapErr};
+use tfxed_core::diagnostic::Diagnostic;
+use tfxed_core::format::format_dsl;
+use crate::args::FmtArgs;
+
+/// Formats each file in place, or stdin to stdout. With `--check`, lists the
+/// files that would change instead, failing if there are any.
+pub fn fmt(args: &FmtArgs) -> Result<()> {
+    let stdin = Path::new("-");
+    if args.files.is_empty() || args.files == [stdin] {
+        return fmt_stdin(args.check);
+    }
+
+    let mut unformatted = 0;
+    for path in &args.files {
+        let source = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("failed to read {}", path.display()))?;
+        let formatted = format(&source)
+            .wrap_err_with(|| format!("failed to format {}", path.display()))?;
+
+        if formatted == source {
+            continue;
+        }
+        match args.check {
+            true => {
+                println!("{}", path.display());
+                unformatted += 1;
+            }
+            false => std::fs::write(path, formatted)
+                .wrap_err_with(|| format!("failed to write {}", path.display()))?,
+        }
+    }
+
+    match unformatted {
+        0 => Ok(()),
+        n => bail!("{n} of {} files aren't formatted", args.files.len()),
+    }
+}
+
+fn fmt_stdin(check: bool) -> Result<()> {
+    let mut source = String::new();
+    std::io::stdin().read_to_string(&mut source).wrap_err("failed to read stdin")?;
+    let formatted = format(&source)?;
+
+    match check {
+        true if formatted != source => bail!("stdin isn't formatted"),
+        true  => Ok(()),
+        false => Ok(std::io::stdout().write_all(formatted.as_bytes())?),
+    }
+}
+
+fn format(source: &str) -> Result<String> {
+    format_dsl(source).map_err(|diagnostics| eyre!(diagnostics.iter()
+        .map(Diagnostic::to_string)
+        .collect::<Vec<_>>()
+        .join("\n")))
+}
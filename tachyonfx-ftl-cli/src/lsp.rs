@@ -0,0 +1,318 @@
+//! A language server for the effect DSL over stdio. Diagnostics come from the
+//! tachyonfx compiler, as in the previewer; completion, hover and signature
+//! help from the [catalog](tfxed_core::catalog).
+
+use std::collections::HashMap;
+use color_eyre::eyre::Result;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{Completion, HoverRequest, Request as _, SignatureHelpRequest};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse,
+    CompletionTextEdit, DiagnosticRelatedInformation, DiagnosticSeverity, Documentation, Hover,
+    HoverContents, HoverParams, HoverProviderCapability, Location, MarkupContent, MarkupKind,
+    ParameterInformation, ParameterLabel, Position, PublishDiagnosticsParams, Range,
+    ServerCapabilities, SignatureHelp, SignatureHelpOptions, SignatureHelpParams,
+    SignatureInformation, TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Uri,
+};
+use tfxed_core::catalog::{self, Symbol, SymbolKind};
+use tfxed_core::diagnostic::{compile_dsl, Diagnostic, Severity, Span};
+
+/// Serves requests until the client shuts the server down.
+pub fn run() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+
+    connection.initialize(serde_json::to_value(capabilities())?)?;
+    Server::default().serve(&connection)?;
+
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![".".to_string(), ":".to_string()]),
+            ..Default::default()
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        signature_help_provider: Some(SignatureHelpOptions {
+            trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// The open documents, by URI.
+#[derive(Default)]
+struct Server {
+    documents: HashMap<Uri, String>,
+}
+
+impl Server {
+    fn serve(&mut self, connection: &Connection) -> Result<()> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        break;
+                    }
+                    connection.sender.send(self.handle_request(request).into())?;
+                }
+                Message::Notification(notification) => {
+                    if let Some(diagnostics) = self.handle_notification(notification) {
+                        let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), diagnostics);
+                        connection.sender.send(notification.into())?;
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            Completion::METHOD           => self.respond::<Completion>(request, Self::completion),
+            HoverRequest::METHOD         => self.respond::<HoverRequest>(request, Self::hover),
+            SignatureHelpRequest::METHOD => self.respond::<SignatureHelpRequest>(request, Self::signature_help),
+            method => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("unsupported request: {method}"),
+            ),
+        }
+    }
+
+    fn respond<R: lsp_types::request::Request>(
+        &self,
+        request: Request,
+        f: impl FnOnce(&Self, R::Params) -> R::Result,
+    ) -> Response {
+        match serde_json::from_value(request.params) {
+            Ok(params) => Response::new_ok(request.id, f(self, params)),
+            Err(e)     => Response::new_err(request.id, ErrorCode::InvalidParams as i32, e.to_string()),
+        }
+    }
+
+    /// Updates the documents; returns the diagnostics to publish for the
+    /// document that changed.
+    fn handle_notification(&mut self, notification: Notification) -> Option<PublishDiagnosticsParams> {
+        let params = notification.params;
+        let uri = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: lsp_types::DidOpenTextDocumentParams = serde_json::from_value(params).ok()?;
+                self.documents.insert(params.text_document.uri.clone(), params.text_document.text);
+                params.text_document.uri
+            }
+            DidChangeTextDocument::METHOD => {
+                // full sync; the last change holds the whole document
+                let params: lsp_types::DidChangeTextDocumentParams = serde_json::from_value(params).ok()?;
+                let text = params.content_changes.into_iter().last()?.text;
+                self.documents.insert(params.text_document.uri.clone(), text);
+                params.text_document.uri
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: lsp_types::DidCloseTextDocumentParams = serde_json::from_value(params).ok()?;
+                self.documents.remove(&params.text_document.uri);
+                return Some(PublishDiagnosticsParams::new(params.text_document.uri, Vec::new(), None));
+            }
+            _ => return None,
+        };
+
+        let source = &self.documents[&uri];
+        let diagnostics = compile_dsl(source)
+            .err()
+            .unwrap_or_default()
+            .iter()
+            .map(|d| lsp_diagnostic(&uri, source, d))
+            .collect();
+
+        Some(PublishDiagnosticsParams::new(uri, diagnostics, None))
+    }
+
+    fn completion(&self, params: CompletionParams) -> Option<CompletionResponse> {
+        let position = params.text_document_position;
+        let source = self.documents.get(&position.text_document.uri)?;
+        let offset = offset_at(source, position.position);
+
+        let (start, partial) = catalog::partial_path_at(source, offset);
+        let range = Range::new(position_at(source, start), position.position);
+
+        let items = catalog::completions(partial).into_iter()
+            .map(|completion| CompletionItem {
+                label: completion.text.clone(),
+                kind: Some(completion_kind(completion.symbol)),
+                detail: Some(completion.symbol.signature()),
+                documentation: Some(Documentation::String(completion.symbol.doc.to_string())),
+                filter_text: Some(completion.text.clone()),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(range, completion.text))),
+                ..Default::default()
+            })
+            .collect();
+
+        Some(CompletionResponse::Array(items))
+    }
+
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let position = params.text_document_position_params;
+        let source = self.documents.get(&position.text_document.uri)?;
+
+        let (symbol, range) = catalog::symbol_at(source, offset_at(source, position.position))?;
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: format!("```rust\n{}\n```\n{}", symbol.signature(), symbol.doc),
+            }),
+            range: Some(Range::new(position_at(source, range.start), position_at(source, range.end))),
+        })
+    }
+
+    fn signature_help(&self, params: SignatureHelpParams) -> Option<SignatureHelp> {
+        let position = params.text_document_position_params;
+        let source = self.documents.get(&position.text_document.uri)?;
+
+        let call = catalog::call_at(source, offset_at(source, position.position))?;
+        let parameters = call.symbol.params.unwrap_or_default().iter()
+            .map(|p| ParameterInformation {
+                label: ParameterLabel::Simple(format!("{}: {}", p.name, p.ty)),
+                documentation: None,
+            })
+            .collect();
+
+        let active_parameter = Some(call.active_param as u32);
+        Some(SignatureHelp {
+            signatures: vec![SignatureInformation {
+                label: call.symbol.signature(),
+                documentation: Some(Documentation::String(call.symbol.doc.to_string())),
+                parameters: Some(parameters),
+                active_parameter,
+            }],
+            active_signature: Some(0),
+            active_parameter,
+        })
+    }
+}
+
+fn completion_kind(symbol: &Symbol) -> CompletionItemKind {
+    match symbol.kind {
+        SymbolKind::Effect   => CompletionItemKind::FUNCTION,
+        SymbolKind::Method   => CompletionItemKind::METHOD,
+        SymbolKind::Function => CompletionItemKind::CONSTRUCTOR,
+        SymbolKind::Variant  => CompletionItemKind::ENUM_MEMBER,
+    }
+}
+
+fn lsp_diagnostic(uri: &Uri, source: &str, diagnostic: &Diagnostic) -> lsp_types::Diagnostic {
+    let message = std::iter::once(diagnostic.message.clone())
+        .chain(diagnostic.hints.iter().map(|hint| format!("hint: {hint}")))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let related = diagnostic.related.iter()
+        .map(|related| DiagnosticRelatedInformation {
+            location: Location::new(uri.clone(), span_range(source, related.span)),
+            message: related.message.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    lsp_types::Diagnostic {
+        range: diagnostic.span.map(|span| span_range(source, span)).unwrap_or_default(),
+        severity: Some(match diagnostic.severity {
+            Severity::Error   => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+        }),
+        source: Some("tfxed".to_string()),
+        message,
+        related_information: (!related.is_empty()).then_some(related),
+        ..Default::default()
+    }
+}
+
+// LSP positions count UTF-16 code units, while spans count characters and the
+// catalog works with byte offsets.
+
+fn span_range(source: &str, span: Span) -> Range {
+    let position = |line: usize, column: usize| {
+        let text = source.lines().nth(line - 1).unwrap_or_default();
+        let character = text.chars().take(column - 1).map(char::len_utf16).sum::<usize>();
+        Position::new(line as u32 - 1, character as u32)
+    };
+
+    Range::new(position(span.start_line, span.start_column), position(span.end_line, span.end_column))
+}
+
+/// the byte offset of `position`, clamped to the end of its line
+fn offset_at(source: &str, position: Position) -> usize {
+    let line_start: usize = source.split_inclusive('\n')
+        .take(position.line as usize)
+        .map(str::len)
+        .sum();
+
+    let mut units = 0;
+    source[line_start..].char_indices()
+        .find(|&(_, c)| {
+            units += c.len_utf16();
+            c == '\n' || units > position.character as usize
+        })
+        .map(|(i, _)| line_start + i)
+        .unwrap_or(source.len())
+}
+
+fn position_at(source: &str, offset: usize) -> Position {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let character = before[line_start..].chars().map(char::len_utf16).sum::<usize>();
+
+    Position::new(before.matches('\n').count() as u32, character as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `é` is two bytes and one UTF-16 unit, `🦀` four bytes and two units
+    const SOURCE: &str = "let é = 1;\nfx::🦀(é)\n";
+
+    #[test]
+    fn offsets_and_positions_count_utf16_units() {
+        let cases = [
+            (Position::new(0, 0), 0),
+            (Position::new(0, 5), 6),   // after `é`
+            (Position::new(1, 4), 16),  // before `🦀`
+            (Position::new(1, 6), 20),  // after `🦀`
+            (Position::new(2, 0), SOURCE.len()),
+        ];
+
+        for (position, offset) in cases {
+            assert_eq!(offset_at(SOURCE, position), offset, "{position:?}");
+            assert_eq!(position_at(SOURCE, offset), position, "{offset}");
+        }
+    }
+
+    #[test]
+    fn offsets_are_clamped_to_the_end_of_the_line() {
+        assert_eq!(offset_at(SOURCE, Position::new(0, 80)), 11);
+        assert_eq!(offset_at(SOURCE, Position::new(1, 80)), SOURCE.len() - 1);
+        assert_eq!(offset_at(SOURCE, Position::new(9, 0)), SOURCE.len());
+        assert_eq!(offset_at("fx::dissolve", Position::new(0, 80)), 12);
+    }
+
+    #[test]
+    fn spans_convert_to_utf16_ranges() {
+        let start = SOURCE.find('🦀').unwrap();
+        let span = Span::from_offsets(SOURCE, start, SOURCE.rfind(')').unwrap() + 1);
+
+        assert_eq!(span_range(SOURCE, span), Range::new(Position::new(1, 4), Position::new(1, 9)));
+
+        let span = Span::from_offsets(SOURCE, 4, 14);
+        assert_eq!(span_range(SOURCE, span), Range::new(Position::new(0, 4), Position::new(1, 2)));
+    }
+}
@@ -1,35 +1,72 @@
+mod args;
+mod canvas;
+mod capture;
+mod completions;
 mod event_handler;
+mod file_watcher;
+mod fmt;
+mod lsp;
+mod render;
+mod snapshot;
 mod tui;
 
-use ratatui::style::Stylize;
 use std::io::Stdout;
-use ansi_to_tui::IntoText;
 
+use crate::args::{Args, Command};
 use crate::event_handler::EventHandler;
+use crate::file_watcher::WatchedFile;
 use crate::tui::Tui;
+use clap::Parser;
 use color_eyre::eyre::{Result, WrapErr};
 use ratatui::backend::CrosstermBackend;
+use tachyonfx::Duration;
 use tfxed_core::{App, AppEvent, Dispatcher};
 
-fn init_tui() -> Result<Tui<CrosstermBackend<Stdout>>> {
+fn init_tui(watched_files: Vec<WatchedFile>) -> Result<Tui<CrosstermBackend<Stdout>>> {
     let terminal = terminal()?;
-    let events = EventHandler::new(std::time::Duration::from_millis(33));
+    let events = EventHandler::new(std::time::Duration::from_millis(33), watched_files);
     Ok(Tui::new(terminal, events))
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
 
-    let input_file = std::env::args().nth(1).unwrap_or_else(|| {
-        eprintln!("Usage: {} <input_file>", std::env::args().next().unwrap());
-        std::process::exit(1);
-    });
+    let args = Args::parse();
+    match &args.command {
+        Some(Command::Render(render_args))           => render::render(render_args),
+        Some(Command::Test(test_args))               => snapshot::test(test_args),
+        Some(Command::Lsp)                           => lsp::run(),
+        Some(Command::Completions(completions_args)) => completions::print(completions_args),
+        Some(Command::Fmt(fmt_args))                 => fmt::fmt(fmt_args),
+        None                                         => preview(&args),
+    }
+}
+
+fn preview(args: &Args) -> Result<()> {
+    let canvas = args.canvas();
+    let input = canvas.read()?;
+
+    let dsl = args.dsl()
+        .map(|path| std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("failed to read DSL {}", path.display())))
+        .transpose()?;
 
-    let input = std::fs::read_to_string(input_file)?;
+    let canvas_event = canvas.event();
+    let watched_files = canvas.path()
+        .map(|path| WatchedFile::new(path, canvas_event))
+        .into_iter()
+        .chain(args.dsl().map(|path| WatchedFile::new(path, AppEvent::CompileDsl)))
+        .collect();
 
-    let mut tui = init_tui()?;
+    let mut tui = init_tui(watched_files)?;
     let mut app = App::new(tui.sender());
-    app.sender().dispatch(AppEvent::UpdateCanvas(input));
+    app.sender().dispatch(canvas_event(input));
+    if let Some(dsl) = dsl {
+        app.sender().dispatch(AppEvent::CompileDsl(dsl));
+    }
+    if let Some(delay) = args.loop_delay {
+        app.sender().dispatch(AppEvent::SetLoop(Some(Duration::from_millis(delay))));
+    }
 
     while app.is_running() {
         tui.receive_events(|event| {
@@ -48,7 +85,6 @@ fn main() -> Result<()> {
 }
 
 fn terminal() -> Result<ratatui::DefaultTerminal> {
-    Ok(ratatui::try_init()
-        .wrap_err("failed to initialize terminal")?)
+    ratatui::try_init()
+        .wrap_err("failed to initialize terminal")
 }
-
@@ -0,0 +1,79 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use tachyonfx::Duration;
+use tfxed_core::canvas::buffer_to_ansi;
+use tfxed_core::export::apng::write_apng;
+use tfxed_core::export::asciicast::write_asciicast;
+use tfxed_core::export::gif::write_gif;
+use tfxed_core::export::json::write_json_lines;
+use tfxed_core::export::raster::Rasterizer;
+use tfxed_core::export::svg::write_svg;
+use tfxed_core::headless::{Frame, Headless};
+use crate::args::{RenderArgs, RenderFormat};
+use crate::canvas::CanvasSource;
+
+/// Renders the effect headlessly and writes the frames in the requested format.
+pub fn render(args: &RenderArgs) -> Result<()> {
+    let mut headless = load(&args.canvas, &args.dsl)?
+        .frame_duration(Duration::from_millis(args.frame_ms()));
+
+    let duration = args.duration
+        .map(Duration::from_millis)
+        .or_else(|| headless.effect_duration())
+        .filter(|d| *d > Duration::ZERO)
+        .ok_or_else(|| eyre!("the effect has no fixed duration; pass --duration"))?;
+
+    let frames = headless.record(duration);
+    let out = args.out();
+    let rasterizer = Rasterizer::new(args.cell_size.0, args.cell_size.1);
+    match args.format {
+        RenderFormat::Ansi      => write_ansi_frames(&frames, &out)?,
+        RenderFormat::Asciicast => write_asciicast(&frames, create(&out)?)
+            .wrap_err_with(|| format!("failed to write {}", out.display()))?,
+        RenderFormat::Svg       => write_svg(&frames, create(&out)?)
+            .wrap_err_with(|| format!("failed to write {}", out.display()))?,
+        RenderFormat::Gif       => write_gif(&frames, &rasterizer, create(&out)?)
+            .wrap_err_with(|| format!("failed to write {}", out.display()))?,
+        RenderFormat::Apng      => write_apng(&frames, &rasterizer, create(&out)?)
+            .wrap_err_with(|| format!("failed to write {}", out.display()))?,
+        RenderFormat::JsonLines => write_json_lines(&frames, create(&out)?)
+            .wrap_err_with(|| format!("failed to write {}", out.display()))?,
+    }
+
+    eprintln!("wrote {} frames to {}", frames.len(), out.display());
+    Ok(())
+}
+
+/// reads the canvas and DSL file and sets up a headless app
+pub fn load(canvas: &CanvasSource, dsl: &Path) -> Result<Headless> {
+    let canvas_src = canvas.read()?;
+    let dsl_src = std::fs::read_to_string(dsl)
+        .wrap_err_with(|| format!("failed to read DSL {}", dsl.display()))?;
+
+    let headless = match canvas {
+        CanvasSource::Layout(_) => Headless::with_layout(&canvas_src, &dsl_src),
+        _                       => Headless::new(&canvas_src, &dsl_src),
+    };
+
+    headless.wrap_err_with(|| format!("failed to load {canvas} and {}", dsl.display()))
+}
+
+/// writes every frame as an ANSI-escaped text file, `frame_00000.ans` and onwards
+fn write_ansi_frames(frames: &[Frame], dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)
+        .wrap_err_with(|| format!("failed to create {}", dir.display()))?;
+
+    frames.iter().enumerate().try_for_each(|(i, frame)| {
+        let path = dir.join(format!("frame_{i:05}.ans"));
+        std::fs::write(&path, buffer_to_ansi(&frame.buffer))
+            .wrap_err_with(|| format!("failed to write {}", path.display()))
+    })
+}
+
+fn create(path: &Path) -> Result<BufWriter<File>> {
+    File::create(path)
+        .map(BufWriter::new)
+        .wrap_err_with(|| format!("failed to create {}", path.display()))
+}
@@ -0,0 +1,68 @@
+use color_eyre::eyre::{bail, Result, WrapErr};
+use tfxed_core::snapshot::{Outcome, SnapshotCase};
+use crate::args::TestArgs;
+
+/// Checks every case against its snapshot, or accepts the rendered frames as
+/// the new snapshots. Fails if any case differs, has no snapshot or can't be
+/// rendered.
+pub fn test(args: &TestArgs) -> Result<()> {
+    let cases = SnapshotCase::discover(&args.dir)
+        .wrap_err_with(|| format!("failed to load cases from {}", args.dir.display()))?;
+
+    if cases.is_empty() {
+        bail!("no cases found in {}", args.dir.display());
+    }
+
+    let mut failed = 0;
+    for case in &cases {
+        let passed = match args.accept {
+            true  => accept(case),
+            false => check(case),
+        };
+        if !passed {
+            failed += 1;
+        }
+    }
+
+    match failed {
+        0 => Ok(()),
+        n => bail!("{n} of {} cases failed", cases.len()),
+    }
+}
+
+fn accept(case: &SnapshotCase) -> bool {
+    match case.accept() {
+        Ok(()) => {
+            println!("accepted {}", case.name);
+            true
+        }
+        Err(e) => {
+            println!("ERROR    {}: {e}", case.name);
+            false
+        }
+    }
+}
+
+fn check(case: &SnapshotCase) -> bool {
+    let outcome = match case.check() {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            println!("ERROR    {}: {e}", case.name);
+            return false;
+        }
+    };
+
+    match &outcome {
+        Outcome::Passed   => println!("ok       {}", case.name),
+        Outcome::Missing  => println!("MISSING  {} (run with --accept to write the snapshot)", case.name),
+        Outcome::Unstable => println!("UNSTABLE {} (renders differently on every run)", case.name),
+        Outcome::Failed(mismatches) => {
+            println!("FAILED   {}", case.name);
+            for mismatch in mismatches {
+                println!("  {}", mismatch.to_string().replace('\n', "\n  "));
+            }
+        }
+    }
+
+    matches!(outcome, Outcome::Passed)
+}
@@ -1,12 +1,8 @@
 use std::io;
 use std::sync::mpsc;
 use crate::event_handler::EventHandler;
-use ratatui::layout::Size;
 use ratatui::{Frame, Terminal};
 use ratatui::prelude::Backend;
-
-#[cfg(feature = "web-backend")]
-use ratzilla::WebRenderer;
 use tfxed_core::AppEvent;
 
 /// Representation of a terminal user interface.
@@ -45,10 +41,6 @@ impl<BACKEND: Backend + 'static> Tui<BACKEND> {
     //     self.terminal.draw_web(render_ui);
     // }
 
-    pub fn size(&self) -> Size {
-        self.terminal.size().unwrap()
-    }
-
     /// iterates over all currently available events; waits
     /// until at least one event is available.
     pub fn receive_events<F>(&self, mut f: F)
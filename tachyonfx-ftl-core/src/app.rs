@@ -1,36 +1,62 @@
-use std::error::Error;
 use std::sync::mpsc::Sender;
-use ansi_to_tui::IntoText;
 use ratatui::buffer::Buffer;
 use ratatui::Frame;
-use ratatui::layout::{Alignment, Offset, Rect};
-use ratatui::prelude::{Color, style::Stylize, Style};
-use ratatui::text::Text;
-use ratatui::widgets::{Block, BorderType, Paragraph, Widget};
-use tachyonfx::{ref_count, BufferRenderer, CenteredShrink, Duration, Effect, EffectManager, RefCount};
-use tachyonfx::dsl::EffectDsl;
+use ratatui::layout::{Offset, Rect, Size};
+use ratatui::prelude::Style;
+use ratatui::widgets::Widget;
+use tachyonfx::{ref_count, BufferRenderer, Duration, EffectManager, RefCount};
 use tachyonfx::fx::consume_tick;
+use crate::canvas::{parse_canvas, CanvasError};
+use crate::canvas::layout::render_layout;
+use crate::clock::{Clock, RealTimeClock};
+use crate::diagnostic::{compile_dsl, Diagnostic, DiagnosticSource};
+#[cfg(all(feature = "web-backend", target_arch = "wasm32"))]
+use crate::diagnostic::diagnostics_json;
 use crate::effects::{display_dsl_error, EffectKind};
 use crate::event::{AppEvent, KeyCode, KeyEvent};
 use crate::gruvbox::Gruvbox;
-use crate::widgets::Ruler;
+use crate::playback::Playback;
+use crate::widgets::{Inspector, Ruler};
 
-#[cfg(feature = "web-backend")]
+#[cfg(all(feature = "web-backend", target_arch = "wasm32"))]
 use wasm_bindgen::prelude::*;
+#[cfg(all(feature = "web-backend", target_arch = "wasm32"))]
 #[wasm_bindgen(js_namespace = window)]
 extern "C" {
     fn dsl_error_callback(error_info: &str);
 }
 
+/// Placement of the effect timeline relative to the canvas.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TimelinePanel {
+    #[default]
+    Hidden,
+    Bottom,
+    Side,
+}
+
+impl TimelinePanel {
+    /// cycles through hidden, bottom and side
+    pub fn next(self) -> Self {
+        match self {
+            TimelinePanel::Hidden => TimelinePanel::Bottom,
+            TimelinePanel::Bottom => TimelinePanel::Side,
+            TimelinePanel::Side   => TimelinePanel::Hidden,
+        }
+    }
+}
+
 pub struct App {
     sender: std::sync::mpsc::Sender<AppEvent>,
     effects: EffectManager<EffectKind>,
+    playback: Playback,
+    timeline_panel: TimelinePanel,
+    inspector_visible: bool,
+    /// lines scrolled past in the error popup
+    error_popup_scroll: RefCount<u16>,
     canvas_base_buf: RefCount<Buffer>,
     canvas_work_buf: RefCount<Buffer>,
-    #[cfg(not(feature = "web-backend"))]
-    last_tick_instant: std::time::Instant,
-    #[cfg(feature = "web-backend")]
-    last_tick_instant: web_time::Instant,
+    clock: Box<dyn Clock>,
     last_tick_duration: Duration,
     counter: usize,
     is_running: bool,
@@ -42,18 +68,16 @@ impl App {
         let canvas_base_buf = ref_count(Buffer::empty(area));
         let canvas_work_buf = ref_count(Buffer::empty(area));
 
-        #[cfg(not(feature = "web-backend"))]
-        let last_tick_instant = std::time::Instant::now();
-
-        #[cfg(feature = "web-backend")]
-        let last_tick_instant = web_time::Instant::now();
-
         Self {
             sender,
             effects: Default::default(),
+            playback: Playback::default(),
+            timeline_panel: TimelinePanel::default(),
+            inspector_visible: false,
+            error_popup_scroll: ref_count(0),
             canvas_base_buf,
             canvas_work_buf,
-            last_tick_instant,
+            clock: Box::new(RealTimeClock::default()),
             last_tick_duration: Duration::default(),
             counter: 0,
             is_running: true,
@@ -76,18 +100,85 @@ impl App {
         self.reset_canvas_work_buffer();
         self.update_effects();
 
-        let canvas_size = self.canvas_work_buf
-            .borrow()
-            .area()
-            .as_size();
+        let canvas_size = self.canvas_size();
 
-        Ruler::new(canvas_size)
-            .style(Style::new().fg(Gruvbox::dark2()))
-            .render(frame.area(), &mut frame.buffer_mut());
+        let ruler = Ruler::new(canvas_size)
+            .style(Style::new().fg(Gruvbox::dark2()));
+        let ruler_area = ruler.ruler_area();
+        ruler.render(frame.area(), frame.buffer_mut());
 
         self.canvas_work_buf.borrow()
-            .render_buffer(Offset { x: 2, y: 2 }, &mut frame.buffer_mut());
+            .render_buffer(Offset { x: 2, y: 2 }, frame.buffer_mut());
         // .render_buffer(Offset { x: x as _, y: y as _ }, &mut frame.buffer_mut());
+
+        self.render_panels(ruler_area, frame);
+    }
+
+    /// Renders the timeline and the inspector next to or below the ruler. The
+    /// inspector takes whichever panel isn't used by the timeline.
+    fn render_panels(&self, ruler_area: Rect, frame: &mut Frame) {
+        let screen = frame.area();
+        let panel_area = |panel: TimelinePanel, height: u16| match panel {
+            TimelinePanel::Hidden => Rect::default(),
+            TimelinePanel::Bottom => Rect {
+                x: screen.x + 2,
+                y: ruler_area.bottom() + 1,
+                width: screen.width.saturating_sub(4),
+                height,
+            },
+            TimelinePanel::Side => Rect {
+                x: ruler_area.right() + 2,
+                y: screen.y + 2,
+                width: screen.width.saturating_sub(ruler_area.right() + 4),
+                height,
+            },
+        };
+
+        let style = Style::new().fg(Gruvbox::light3());
+        let playhead = self.playback.elapsed();
+
+        let timeline = self.playback.timeline()
+            .filter(|_| self.timeline_panel != TimelinePanel::Hidden);
+
+        if let Some(timeline) = timeline {
+            let timeline = timeline.clone().playhead(playhead);
+            let area = panel_area(self.timeline_panel, timeline.height());
+            timeline.render(area.intersection(screen), frame.buffer_mut());
+        }
+
+        let tree = self.playback.effect_tree()
+            .filter(|_| self.inspector_visible);
+
+        if let Some(tree) = tree {
+            let inspector = Inspector::new(tree)
+                .style(style);
+
+            let panel = match self.timeline_panel {
+                TimelinePanel::Side => TimelinePanel::Bottom,
+                _                   => TimelinePanel::Side,
+            };
+
+            let area = panel_area(panel, inspector.height());
+            inspector.render(area.intersection(screen), frame.buffer_mut());
+        }
+    }
+
+    pub fn timeline_panel(&self) -> TimelinePanel {
+        self.timeline_panel
+    }
+
+    /// Advances the effects and renders only the canvas, without the ruler or
+    /// any panels, into `buf` at its origin.
+    pub fn render_canvas(&mut self, buf: &mut Buffer) {
+        self.reset_canvas_work_buffer();
+        self.update_effects();
+
+        self.canvas_work_buf.borrow()
+            .render_buffer(Offset::default(), buf);
+    }
+
+    pub fn canvas_size(&self) -> Size {
+        self.canvas_work_buf.borrow().area().as_size()
     }
 
     /// updates the work buffer with the contents of the base buffer.
@@ -102,9 +193,21 @@ impl App {
         let mut buf = self.canvas_work_buf.borrow_mut();
         let area = *buf.area();
 
+        self.playback.process(d, &mut buf, area);
         self.effects.process_effects(d, &mut buf, area);
     }
 
+    pub fn playback(&self) -> &Playback {
+        &self.playback
+    }
+
+    /// Moves the editor effect to `t`. The effect is rebuilt from its compiled
+    /// state and fast-forwarded over a copy of the canvas, so the resulting
+    /// frame is the same regardless of the current playback position.
+    pub fn seek(&mut self, t: Duration) {
+        self.playback.seek(t, &self.canvas_base_buf.borrow());
+    }
+
     pub fn is_running(&self) -> bool {
         self.is_running
     }
@@ -113,22 +216,17 @@ impl App {
         self.counter
     }
 
-    #[cfg(not(feature = "web-backend"))]
-    pub fn update_time(&mut self) -> Duration {
-        let now = std::time::Instant::now();
-        let last_frame_duration: Duration = now.duration_since(self.last_tick_instant).into();
-        self.last_tick_instant = now;
-        self.last_tick_duration = last_frame_duration;
-        last_frame_duration
+    /// Replaces the source of frame time; the app starts out with a
+    /// [RealTimeClock].
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Box::new(clock);
     }
 
-    #[cfg(feature = "web-backend")]
+    /// Takes the time passed since the previous frame from the clock; it's
+    /// applied to the effects by the next render.
     pub fn update_time(&mut self) -> Duration {
-        let now = web_time::Instant::now();
-        let last_frame_duration: Duration = now.duration_since(self.last_tick_instant).into();
-        self.last_tick_instant = now;
-        self.last_tick_duration = last_frame_duration;
-        last_frame_duration
+        self.last_tick_duration = self.clock.tick();
+        self.last_tick_duration
     }
 
     pub fn apply_event(&mut self, event: AppEvent) {
@@ -139,79 +237,145 @@ impl App {
             AppEvent::KeyPress(KeyEvent { key_code: KeyCode::Esc, .. }) => {
                 self.is_running = false;
             }
-            AppEvent::KeyPress(KeyEvent { key_code, .. }) => {
-                self.counter = self.counter + 1;
+            AppEvent::KeyPress(_) => {
+                self.counter += 1;
+            }
+            AppEvent::UpdateCanvas(s) => {
+                if let Err(e) = self.update_canvas(&s) {
+                    self.report_canvas_error(e);
+                }
+            }
+            AppEvent::UpdateCanvasLayout(s) => {
+                match render_layout(&s) {
+                    Ok(canvas) => self.replace_canvas(canvas),
+                    Err(e)     => self.report_canvas_error(e),
+                }
+            }
+            AppEvent::TogglePause  => self.playback.toggle_pause(),
+            AppEvent::Restart      => self.playback.restart(&self.canvas_base_buf.borrow()),
+            AppEvent::StepForward  => self.playback.step_forward(),
+            AppEvent::StepBackward => self.playback.step_backward(&self.canvas_base_buf.borrow()),
+            AppEvent::SetTimeScale(scale) => self.playback.set_time_scale(scale),
+            AppEvent::IncreaseTimeScale   => self.playback.increase_time_scale(),
+            AppEvent::DecreaseTimeScale   => self.playback.decrease_time_scale(),
+            AppEvent::ToggleSlowMotion    => self.playback.toggle_slow_motion(),
+            AppEvent::Seek(t)             => self.seek(t),
+            AppEvent::SeekForward(d)      => self.seek(self.playback.elapsed() + d),
+            AppEvent::SeekBackward(d)     => {
+                self.seek(self.playback.elapsed().checked_sub(d).unwrap_or_default())
+            }
+            AppEvent::SetLoop(delay)      => self.playback.set_loop(delay),
+            AppEvent::ToggleLoop          => self.playback.toggle_loop(),
+            AppEvent::ToggleTimeline      => self.timeline_panel = self.timeline_panel.next(),
+            AppEvent::SetTimelinePanel(p) => self.timeline_panel = p,
+            AppEvent::ToggleInspector     => self.inspector_visible = !self.inspector_visible,
+            AppEvent::ShowInspector(show) => self.inspector_visible = show,
+            AppEvent::ScrollErrorPopup(n) => {
+                let mut scroll = self.error_popup_scroll.borrow_mut();
+                *scroll = scroll.saturating_add_signed(n);
             }
-            AppEvent::UpdateCanvas(s) => self.update_canvas(s),
+            AppEvent::DismissErrorPopup   => self.dismiss_error_popup(),
             AppEvent::CompileDsl(dsl) => {
-                // Compile the DSL and update the canvas
-                let effect = EffectDsl::new()
-                    .compiler()
-                    .compile(dsl.as_str());
-
-                match effect {
+                match compile_dsl(&dsl) {
                     Ok(effect) => {
-                        use EffectKind::*;
-
-                        // register compiled effect
-                        self.effects.add_unique_effect(Editor, effect);
+                        // replace the running effect
+                        self.playback.load(dsl, effect);
 
-                        // clear any old error popup
-                        self.effects.add_unique_effect(DslErrorPopup, consume_tick());
-                    }
-                    Err(e)     => {
-                        println!("Error: {}", e);
-                        println!("Error (context): {}", e.context());
-                        
-                        // Create JSON-formatted error info
-                        let error_info = format!(
-                            "{{\"message\":\"{}\",\"line\":{},\"column\":{},\"line_end\":{},\"column_end\":{}}}",
-                            e.source.to_string().replace("\"", "\\\""), // Escape quotes
-                            e.start_line(),
-                            e.start_column(),
-                            e.end_line(),
-                            e.end_column() - 1
-                        );
-
-                        #[cfg(feature = "web-backend")]
-                        dsl_error_callback(&error_info);
-
-                        self.display_error_popup(
-                            e.source.to_string(),
-                            e.context(),
-                            (e.start_line() as _, e.start_column() as _),
-                        )
+                        self.dismiss_error_popup();
                     }
+                    Err(diagnostics) => self.report_diagnostics(&diagnostics, &dsl),
                 }
             }
             _ => {}
         }
     }
 
-    fn display_error_popup(
-        &mut self,
-        error_message: String,
-        referenced_code: String,
-        position: (u32, u32),
-    ) {
+    /// Reports the diagnostics to the web front end and shows them in the error
+    /// popup; `source` is the text the diagnostics refer to.
+    fn report_diagnostics(&mut self, diagnostics: &[Diagnostic], source: &str) {
+        #[cfg(all(feature = "web-backend", target_arch = "wasm32"))]
+        dsl_error_callback(&diagnostics_json(diagnostics));
+
+        *self.error_popup_scroll.borrow_mut() = 0;
         let duration = Duration::from_millis(15000);
         self.effects.add_unique_effect(EffectKind::DslErrorPopup,
-            display_dsl_error(duration, error_message, referenced_code, position)
+            display_dsl_error(duration, diagnostics, source, self.error_popup_scroll.clone())
         );
     }
 
-    fn update_canvas(&mut self, source: String) {
-        let input = source.into_text().unwrap_or_else(|_| {
-            eprintln!("Failed to parse input file");
-            std::process::exit(1);
-        });
+    fn dismiss_error_popup(&mut self) {
+        // replaces the popup with an effect that ends immediately
+        self.effects.add_unique_effect(EffectKind::DslErrorPopup, consume_tick());
+    }
+
+    /// Reports a canvas that failed to parse through the same channels as DSL
+    /// errors; the previous canvas stays in place.
+    fn report_canvas_error(&mut self, e: CanvasError) {
+        let diagnostic = Diagnostic::error(DiagnosticSource::Canvas, e.to_string());
+        self.report_diagnostics(&[diagnostic], "");
+    }
+
+    /// Replaces the canvas; on error, the current canvas is kept.
+    fn update_canvas(&mut self, source: &str) -> Result<(), CanvasError> {
+        let input = parse_canvas(source)?;
 
         let w = input.lines.iter().map(|line| line.width()).max().unwrap_or(0);
         let h = input.lines.len();
 
         let area = Rect::new(0, 0, w as _, h as _);
-        self.resize_canvas(area);
+        let mut canvas = Buffer::empty(area);
+        input.render(area, &mut canvas);
 
-        input.render(area, &mut self.canvas_base_buf.borrow_mut());
+        self.replace_canvas(canvas);
+        Ok(())
     }
-}
\ No newline at end of file
+
+    fn replace_canvas(&mut self, canvas: Buffer) {
+        // the running effect is left untouched; only the base buffer is swapped
+        if canvas.area != *self.canvas_base_buf.borrow().area() {
+            self.resize_canvas(canvas.area);
+        }
+
+        *self.canvas_base_buf.borrow_mut() = canvas;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use crate::clock::ManualClock;
+    use super::*;
+
+    fn app() -> App {
+        let (sender, _) = mpsc::channel();
+        let mut app = App::new(sender);
+        app.set_clock(ManualClock::default());
+        app
+    }
+
+    fn rows(buf: &Buffer) -> Vec<String> {
+        buf.content()
+            .chunks(buf.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn an_invalid_canvas_is_reported_and_the_previous_canvas_kept() {
+        let mut app = app();
+        let canvas = vec!["#".repeat(60); 12].join("\n");
+        app.apply_event(AppEvent::UpdateCanvas(canvas));
+        app.apply_event(AppEvent::UpdateCanvas("x".repeat(70_000)));
+
+        assert!(app.is_running());
+        assert_eq!(app.canvas_size(), Size::new(60, 12));
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 60, 12));
+        app.update_time();
+        app.render_canvas(&mut buf);
+
+        let rows = rows(&buf);
+        assert!(rows.iter().any(|row| row.contains("canvas of 70000x1 cells is too large")), "{rows:#?}");
+        assert!(rows.iter().any(|row| row.starts_with("####")));
+    }
+}
@@ -0,0 +1,600 @@
+//! Canvases described as a tree of ratatui widgets, so that effects can be
+//! previewed against the layouts drawn by real applications. Layouts are
+//! written in [RON](https://github.com/ron-rs/ron):
+//!
+//! ```text
+//! (
+//!     width: 48,
+//!     height: 12,
+//!     style: (fg: "#d5c4a1", bg: "#1d2021"),
+//!     root: Split(
+//!         direction: Vertical,
+//!         constraints: [Length(3), Fill(1)],
+//!         children: [
+//!             Tabs(titles: ["Overview", "Logs"], selected: 0, block: (title: "app")),
+//!             Paragraph(text: "hello\nworld", wrap: true, block: (border_type: Rounded)),
+//!         ],
+//!     ),
+//! )
+//! ```
+//!
+//! `Split` divides its area between its children like [ratatui::layout::Layout];
+//! the widgets are `Block`, `Paragraph`, `List`, `Table`, `Gauge` and `Tabs`.
+//! Optional fields may be left out, and are given without `Some(..)` otherwise.
+//! Colors are ratatui color names, `#rrggbb` or indices; modifiers are lowercase
+//! names such as `"bold"` or `"crossed_out"`.
+
+use std::str::FromStr;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{
+    Block, BorderType, Borders, Gauge, List, ListState, Padding, Paragraph, Row,
+    StatefulWidget, Table, TableState, Tabs, Widget, Wrap,
+};
+use ron::extensions::Extensions;
+use serde::Deserialize;
+use crate::canvas::CanvasError;
+
+/// Parses a widget layout and renders it into a buffer of the layout's size.
+pub fn render_layout(source: &str) -> Result<Buffer, CanvasError> {
+    let layout: WidgetLayout = ron::Options::default()
+        .with_default_extension(Extensions::IMPLICIT_SOME | Extensions::UNWRAP_VARIANT_NEWTYPES)
+        .from_str(source)
+        .map_err(|e| CanvasError::Layout(e.to_string()))?;
+
+    let (width, height) = (layout.width, layout.height);
+    if width as usize * height as usize > u16::MAX as usize {
+        return Err(CanvasError::TooLarge { width: width as _, height: height as _ });
+    }
+
+    let area = Rect::new(0, 0, width, height);
+    let mut buf = Buffer::empty(area);
+    buf.set_style(area, layout.style.to_style());
+    layout.root.render(area, &mut buf)?;
+
+    Ok(buf)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WidgetLayout {
+    width: u16,
+    height: u16,
+    /// base style of the whole canvas
+    #[serde(default)]
+    style: StyleDef,
+    root: Node,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+enum Node {
+    /// divides the area between the children, one constraint per child
+    Split {
+        direction: DirectionDef,
+        constraints: Vec<ConstraintDef>,
+        children: Vec<Node>,
+        #[serde(default)]
+        margin: u16,
+        #[serde(default)]
+        spacing: u16,
+        #[serde(default)]
+        block: Option<BlockDef>,
+    },
+    Block(BlockDef),
+    Paragraph {
+        text: String,
+        #[serde(default)]
+        style: StyleDef,
+        #[serde(default)]
+        alignment: AlignmentDef,
+        #[serde(default)]
+        wrap: bool,
+        #[serde(default)]
+        block: Option<BlockDef>,
+    },
+    List {
+        items: Vec<String>,
+        #[serde(default)]
+        style: StyleDef,
+        #[serde(default)]
+        selected: Option<usize>,
+        #[serde(default)]
+        highlight_style: StyleDef,
+        #[serde(default)]
+        highlight_symbol: Option<String>,
+        #[serde(default)]
+        block: Option<BlockDef>,
+    },
+    Table {
+        #[serde(default)]
+        header: Option<Vec<String>>,
+        rows: Vec<Vec<String>>,
+        /// column widths; columns share the width equally when left out
+        #[serde(default)]
+        widths: Vec<ConstraintDef>,
+        #[serde(default)]
+        style: StyleDef,
+        #[serde(default)]
+        header_style: StyleDef,
+        #[serde(default)]
+        selected: Option<usize>,
+        #[serde(default)]
+        highlight_style: StyleDef,
+        #[serde(default)]
+        highlight_symbol: Option<String>,
+        #[serde(default = "default_column_spacing")]
+        column_spacing: u16,
+        #[serde(default)]
+        block: Option<BlockDef>,
+    },
+    Gauge {
+        /// filled portion, from 0.0 to 1.0
+        ratio: f64,
+        #[serde(default)]
+        label: Option<String>,
+        #[serde(default)]
+        style: StyleDef,
+        #[serde(default)]
+        gauge_style: StyleDef,
+        #[serde(default)]
+        use_unicode: bool,
+        #[serde(default)]
+        block: Option<BlockDef>,
+    },
+    Tabs {
+        titles: Vec<String>,
+        #[serde(default)]
+        selected: usize,
+        #[serde(default)]
+        style: StyleDef,
+        #[serde(default)]
+        highlight_style: StyleDef,
+        #[serde(default)]
+        divider: Option<String>,
+        #[serde(default)]
+        block: Option<BlockDef>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+struct BlockDef {
+    title: Option<String>,
+    title_alignment: AlignmentDef,
+    title_style: StyleDef,
+    borders: Vec<Side>,
+    border_type: BorderTypeDef,
+    border_style: StyleDef,
+    style: StyleDef,
+    /// horizontal and vertical padding inside the borders
+    padding: (u16, u16),
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+struct StyleDef {
+    fg: Option<ColorDef>,
+    bg: Option<ColorDef>,
+    modifiers: Vec<ModifierDef>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "String")]
+struct ColorDef(Color);
+
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "String")]
+struct ModifierDef(Modifier);
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+enum Side {
+    All,
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+enum DirectionDef {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+enum AlignmentDef {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+enum BorderTypeDef {
+    #[default]
+    Plain,
+    Rounded,
+    Double,
+    Thick,
+    QuadrantInside,
+    QuadrantOutside,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+enum ConstraintDef {
+    Length(u16),
+    Min(u16),
+    Max(u16),
+    Percentage(u16),
+    Ratio(u32, u32),
+    Fill(u16),
+}
+
+fn default_column_spacing() -> u16 {
+    1
+}
+
+fn check_constraints(constraints: &[ConstraintDef]) -> Result<(), CanvasError> {
+    match constraints.iter().any(|c| matches!(c, ConstraintDef::Ratio(_, 0))) {
+        true  => Err(CanvasError::Layout("ratio constraint with a denominator of 0".into())),
+        false => Ok(()),
+    }
+}
+
+impl Node {
+    fn render(&self, area: Rect, buf: &mut Buffer) -> Result<(), CanvasError> {
+        match self {
+            Node::Split { direction, constraints, children, margin, spacing, block } => {
+                if constraints.len() != children.len() {
+                    return Err(CanvasError::Layout(format!(
+                        "split has {} constraints for {} children", constraints.len(), children.len()
+                    )));
+                }
+
+                check_constraints(constraints)?;
+
+                let inner = match block {
+                    Some(block) => {
+                        let block = block.to_block();
+                        let inner = block.inner(area);
+                        block.render(area, buf);
+                        inner
+                    }
+                    None => area,
+                };
+
+                let areas = Layout::new((*direction).into(), constraints.iter().map(|&c| Constraint::from(c)))
+                    .margin(*margin)
+                    .spacing(*spacing)
+                    .split(inner);
+
+                return children.iter()
+                    .zip(areas.iter())
+                    .try_for_each(|(child, area)| child.render(*area, buf));
+            }
+            Node::Block(block) => block.to_block().render(area, buf),
+            Node::Paragraph { text, style, alignment, wrap, block } => {
+                let mut paragraph = Paragraph::new(text.as_str())
+                    .style(style.to_style())
+                    .alignment((*alignment).into());
+                if *wrap {
+                    paragraph = paragraph.wrap(Wrap { trim: false });
+                }
+                if let Some(block) = block {
+                    paragraph = paragraph.block(block.to_block());
+                }
+                paragraph.render(area, buf);
+            }
+            Node::List { items, style, selected, highlight_style, highlight_symbol, block } => {
+                let mut list = List::new(items.iter().map(String::as_str))
+                    .style(style.to_style())
+                    .highlight_style(highlight_style.to_style());
+                if let Some(symbol) = highlight_symbol {
+                    list = list.highlight_symbol(symbol.as_str());
+                }
+                if let Some(block) = block {
+                    list = list.block(block.to_block());
+                }
+                let mut state = ListState::default().with_selected(*selected);
+                StatefulWidget::render(list, area, buf, &mut state);
+            }
+            Node::Table {
+                header, rows, widths, style, header_style, selected, highlight_style,
+                highlight_symbol, column_spacing, block,
+            } => {
+                check_constraints(widths)?;
+
+                let rows = rows.iter().map(|row| Row::new(row.iter().map(String::as_str)));
+                let mut table = Table::new(rows, widths.iter().map(|&c| Constraint::from(c)))
+                    .style(style.to_style())
+                    .row_highlight_style(highlight_style.to_style())
+                    .column_spacing(*column_spacing);
+                if let Some(header) = header {
+                    table = table.header(Row::new(header.iter().map(String::as_str))
+                        .style(header_style.to_style()));
+                }
+                if let Some(symbol) = highlight_symbol {
+                    table = table.highlight_symbol(symbol.as_str());
+                }
+                if let Some(block) = block {
+                    table = table.block(block.to_block());
+                }
+                let mut state = TableState::default().with_selected(*selected);
+                StatefulWidget::render(table, area, buf, &mut state);
+            }
+            Node::Gauge { ratio, label, style, gauge_style, use_unicode, block } => {
+                if !(0.0..=1.0).contains(ratio) {
+                    return Err(CanvasError::Layout(format!(
+                        "gauge ratio {ratio} is outside of 0.0 to 1.0"
+                    )));
+                }
+
+                let mut gauge = Gauge::default()
+                    .ratio(*ratio)
+                    .style(style.to_style())
+                    .gauge_style(gauge_style.to_style())
+                    .use_unicode(*use_unicode);
+                if let Some(label) = label {
+                    gauge = gauge.label(label.as_str());
+                }
+                if let Some(block) = block {
+                    gauge = gauge.block(block.to_block());
+                }
+                gauge.render(area, buf);
+            }
+            Node::Tabs { titles, selected, style, highlight_style, divider, block } => {
+                let mut tabs = Tabs::new(titles.iter().map(String::as_str))
+                    .select(*selected)
+                    .style(style.to_style())
+                    .highlight_style(highlight_style.to_style());
+                if let Some(divider) = divider {
+                    tabs = tabs.divider(divider.as_str());
+                }
+                if let Some(block) = block {
+                    tabs = tabs.block(block.to_block());
+                }
+                tabs.render(area, buf);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for BlockDef {
+    fn default() -> Self {
+        Self {
+            title: None,
+            title_alignment: AlignmentDef::default(),
+            title_style: StyleDef::default(),
+            borders: vec![Side::All],
+            border_type: BorderTypeDef::default(),
+            border_style: StyleDef::default(),
+            style: StyleDef::default(),
+            padding: (0, 0),
+        }
+    }
+}
+
+impl BlockDef {
+    fn to_block(&self) -> Block<'_> {
+        let borders = self.borders.iter()
+            .fold(Borders::NONE, |borders, side| borders | Borders::from(*side));
+
+        let block = Block::new()
+            .borders(borders)
+            .border_type(self.border_type.into())
+            .border_style(self.border_style.to_style())
+            .title_alignment(self.title_alignment.into())
+            .title_style(self.title_style.to_style())
+            .style(self.style.to_style())
+            .padding(Padding::symmetric(self.padding.0, self.padding.1));
+
+        match &self.title {
+            Some(title) => block.title(title.as_str()),
+            None        => block,
+        }
+    }
+}
+
+impl StyleDef {
+    fn to_style(&self) -> Style {
+        let style = Style::new().add_modifier(self.modifiers.iter()
+            .fold(Modifier::empty(), |modifiers, m| modifiers | m.0));
+
+        let style = match &self.fg {
+            Some(fg) => style.fg(fg.0),
+            None     => style,
+        };
+        match &self.bg {
+            Some(bg) => style.bg(bg.0),
+            None     => style,
+        }
+    }
+}
+
+impl TryFrom<String> for ColorDef {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Color::from_str(&s)
+            .map(ColorDef)
+            .map_err(|_| format!("invalid color '{s}'; expected a name, #rrggbb or an index"))
+    }
+}
+
+impl TryFrom<String> for ModifierDef {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Modifier::from_name(&s.to_uppercase())
+            .map(ModifierDef)
+            .ok_or_else(|| format!("invalid modifier '{s}'"))
+    }
+}
+
+impl From<Side> for Borders {
+    fn from(side: Side) -> Self {
+        match side {
+            Side::All    => Borders::ALL,
+            Side::Top    => Borders::TOP,
+            Side::Right  => Borders::RIGHT,
+            Side::Bottom => Borders::BOTTOM,
+            Side::Left   => Borders::LEFT,
+        }
+    }
+}
+
+impl From<DirectionDef> for Direction {
+    fn from(direction: DirectionDef) -> Self {
+        match direction {
+            DirectionDef::Horizontal => Direction::Horizontal,
+            DirectionDef::Vertical   => Direction::Vertical,
+        }
+    }
+}
+
+impl From<AlignmentDef> for Alignment {
+    fn from(alignment: AlignmentDef) -> Self {
+        match alignment {
+            AlignmentDef::Left   => Alignment::Left,
+            AlignmentDef::Center => Alignment::Center,
+            AlignmentDef::Right  => Alignment::Right,
+        }
+    }
+}
+
+impl From<BorderTypeDef> for BorderType {
+    fn from(border_type: BorderTypeDef) -> Self {
+        match border_type {
+            BorderTypeDef::Plain           => BorderType::Plain,
+            BorderTypeDef::Rounded         => BorderType::Rounded,
+            BorderTypeDef::Double          => BorderType::Double,
+            BorderTypeDef::Thick           => BorderType::Thick,
+            BorderTypeDef::QuadrantInside  => BorderType::QuadrantInside,
+            BorderTypeDef::QuadrantOutside => BorderType::QuadrantOutside,
+        }
+    }
+}
+
+impl From<ConstraintDef> for Constraint {
+    fn from(constraint: ConstraintDef) -> Self {
+        match constraint {
+            ConstraintDef::Length(n)     => Constraint::Length(n),
+            ConstraintDef::Min(n)        => Constraint::Min(n),
+            ConstraintDef::Max(n)        => Constraint::Max(n),
+            ConstraintDef::Percentage(p) => Constraint::Percentage(p),
+            ConstraintDef::Ratio(n, d)   => Constraint::Ratio(n, d),
+            ConstraintDef::Fill(n)       => Constraint::Fill(n),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(buf: &Buffer) -> Vec<String> {
+        buf.content()
+            .chunks(buf.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect())
+            .collect()
+    }
+
+    fn layout_error(source: &str) -> String {
+        match render_layout(source) {
+            Err(CanvasError::Layout(e)) => e,
+            other                       => panic!("expected a layout error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn renders_the_documented_example() {
+        let buf = render_layout(r##"(
+            width: 48,
+            height: 12,
+            style: (fg: "#d5c4a1", bg: "#1d2021"),
+            root: Split(
+                direction: Vertical,
+                constraints: [Length(3), Fill(1)],
+                children: [
+                    Tabs(titles: ["Overview", "Logs"], selected: 0, block: (title: "app")),
+                    Paragraph(text: "hello\nworld", wrap: true, block: (border_type: Rounded)),
+                ],
+            ),
+        )"##).unwrap();
+
+        assert_eq!(buf.area, Rect::new(0, 0, 48, 12));
+        assert!(buf.content().iter().all(|cell| cell.bg == Color::Rgb(0x1d, 0x20, 0x21)));
+
+        let rows = rows(&buf);
+        assert!(rows[0].starts_with("┌app"));
+        assert!(rows[1].starts_with("│ Overview │ Logs"));
+        assert!(rows[3].starts_with("╭─"));
+        assert!(rows[4].starts_with("│hello "));
+        assert!(rows[5].starts_with("│world "));
+        assert!(rows[11].starts_with("╰─"));
+    }
+
+    #[test]
+    fn splits_the_area_by_the_constraints() {
+        let buf = render_layout(r#"(
+            width: 10,
+            height: 2,
+            root: Split(
+                direction: Horizontal,
+                constraints: [Length(3), Fill(1)],
+                spacing: 1,
+                children: [
+                    Paragraph(text: "abcdef"),
+                    Block(borders: [Left], border_type: Double),
+                ],
+            ),
+        )"#).unwrap();
+
+        assert_eq!(rows(&buf), ["abc ║     ", "    ║     "]);
+    }
+
+    #[test]
+    fn parses_colors_and_modifiers() {
+        let buf = render_layout(r#"(
+            width: 3,
+            height: 1,
+            root: Paragraph(text: "abc", style: (fg: "light_red", bg: "208", modifiers: ["bold", "crossed_out"])),
+        )"#).unwrap();
+
+        let cell = &buf.content()[0];
+        assert_eq!((cell.fg, cell.bg), (Color::LightRed, Color::Indexed(208)));
+        assert_eq!(cell.modifier, Modifier::BOLD | Modifier::CROSSED_OUT);
+    }
+
+    #[test]
+    fn rejects_invalid_layouts() {
+        let root = |root: &str| format!("(width: 10, height: 2, root: {root})");
+
+        assert!(layout_error(&root(r#"Paragraph(text: "a", style: (fg: "reddish"))"#))
+            .contains("invalid color 'reddish'"));
+        assert!(layout_error(&root(r#"Paragraph(text: "a", style: (modifiers: ["shiny"]))"#))
+            .contains("invalid modifier 'shiny'"));
+        assert!(layout_error(&root(r#"Paragraph(text: "a", colour: "red")"#))
+            .contains("colour"));
+        assert_eq!(
+            layout_error(&root("Split(direction: Vertical, constraints: [Fill(1)], children: [])")),
+            "split has 1 constraints for 0 children",
+        );
+        assert_eq!(
+            layout_error(&root("Table(rows: [], widths: [Ratio(1, 0)])")),
+            "ratio constraint with a denominator of 0",
+        );
+        assert_eq!(
+            layout_error(&root("Gauge(ratio: 1.5)")),
+            "gauge ratio 1.5 is outside of 0.0 to 1.0",
+        );
+        assert!(matches!(
+            render_layout("(width: 1000, height: 1000, root: Block())"),
+            Err(CanvasError::TooLarge { width: 1000, height: 1000 })
+        ));
+    }
+}
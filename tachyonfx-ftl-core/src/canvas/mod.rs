@@ -0,0 +1,190 @@
+//! The ANSI-escaped text drawn on the canvas: parsing it, and producing it from
+//! buffers for canvases built in code or from widget layouts.
+
+use std::fmt;
+use std::fmt::Write;
+use ansi_to_tui::IntoText;
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
+use ratatui::text::{Span, Text};
+
+pub mod layout;
+pub mod presets;
+
+/// The canvas source couldn't be turned into a buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CanvasError {
+    /// the source isn't valid ANSI-escaped text
+    Parse(String),
+    /// the text doesn't fit in a buffer
+    TooLarge { width: usize, height: usize },
+    /// the widget layout is invalid; see [layout]
+    Layout(String),
+}
+
+impl fmt::Display for CanvasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CanvasError::Parse(e) => write!(f, "invalid ANSI input: {e}"),
+            CanvasError::TooLarge { width, height } => {
+                write!(f, "canvas of {width}x{height} cells is too large")
+            }
+            CanvasError::Layout(e) => write!(f, "invalid widget layout: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for CanvasError {}
+
+/// Parses `source` into styled text; its width and height fit in a `u16`.
+pub fn parse_canvas(source: &str) -> Result<Text<'static>, CanvasError> {
+    let text = source.into_text()
+        .map_err(|e| CanvasError::Parse(e.to_string()))?;
+
+    let width = text.lines.iter().map(|line| line.width()).max().unwrap_or(0);
+    let height = text.lines.len();
+    if width > u16::MAX as usize || height > u16::MAX as usize {
+        return Err(CanvasError::TooLarge { width, height });
+    }
+
+    Ok(text)
+}
+
+/// Writes `buffer` as ANSI-escaped text, one line per row, which parses back into
+/// the same buffer. Unlike `tachyonfx::render_as_ansi_string`, cells covered by
+/// wide characters are skipped, so wide text keeps its width.
+pub fn buffer_to_ansi(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut ansi = String::new();
+
+    for y in area.top()..area.bottom() {
+        if y > area.top() {
+            ansi.push('\n');
+        }
+
+        let mut style = PLAIN;
+        let mut covered_until = area.left();
+        for x in area.left()..area.right() {
+            if x < covered_until {
+                continue;
+            }
+
+            let cell = &buffer[(x, y)];
+            if (cell.fg, cell.bg, cell.modifier) != style {
+                style = (cell.fg, cell.bg, cell.modifier);
+                ansi.push_str(&sgr(style));
+            }
+
+            ansi.push_str(cell.symbol());
+            covered_until = x + (Span::raw(cell.symbol()).width() as u16).max(1);
+        }
+
+        if style != PLAIN {
+            ansi.push_str("\x1b[0m");
+        }
+    }
+
+    ansi
+}
+
+/// foreground, background and modifiers of a cell
+type CellStyle = (Color, Color, Modifier);
+
+const PLAIN: CellStyle = (Color::Reset, Color::Reset, Modifier::empty());
+
+/// a reset followed by the codes of `style`, as a single escape sequence
+fn sgr((fg, bg, modifier): CellStyle) -> String {
+    const MODIFIERS: [(Modifier, u8); 9] = [
+        (Modifier::BOLD, 1),
+        (Modifier::DIM, 2),
+        (Modifier::ITALIC, 3),
+        (Modifier::UNDERLINED, 4),
+        (Modifier::SLOW_BLINK, 5),
+        (Modifier::RAPID_BLINK, 6),
+        (Modifier::REVERSED, 7),
+        (Modifier::HIDDEN, 8),
+        (Modifier::CROSSED_OUT, 9),
+    ];
+
+    let mut codes = String::from("\x1b[0");
+    for (flag, code) in MODIFIERS {
+        if modifier.contains(flag) {
+            let _ = write!(codes, ";{code}");
+        }
+    }
+    if let Some(fg) = color_code(fg, 30) {
+        let _ = write!(codes, ";{fg}");
+    }
+    if let Some(bg) = color_code(bg, 40) {
+        let _ = write!(codes, ";{bg}");
+    }
+    codes.push('m');
+
+    codes
+}
+
+/// `base` is 30 for the foreground and 40 for the background
+fn color_code(color: Color, base: u8) -> Option<String> {
+    let named = |offset: u8| Some((base + offset).to_string());
+    let bright = |offset: u8| Some((base + 60 + offset).to_string());
+
+    match color {
+        Color::Reset        => None,
+        Color::Black        => named(0),
+        Color::Red          => named(1),
+        Color::Green        => named(2),
+        Color::Yellow       => named(3),
+        Color::Blue         => named(4),
+        Color::Magenta      => named(5),
+        Color::Cyan         => named(6),
+        Color::Gray         => named(7),
+        Color::DarkGray     => bright(0),
+        Color::LightRed     => bright(1),
+        Color::LightGreen   => bright(2),
+        Color::LightYellow  => bright(3),
+        Color::LightBlue    => bright(4),
+        Color::LightMagenta => bright(5),
+        Color::LightCyan    => bright(6),
+        Color::White        => bright(7),
+        Color::Indexed(i)   => Some(format!("{};5;{i}", base + 8)),
+        Color::Rgb(r, g, b) => Some(format!("{};2;{r};{g};{b}", base + 8)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::layout::Rect;
+    use ratatui::style::Style;
+    use ratatui::widgets::Widget;
+    use super::*;
+
+    /// the buffer the app renders `source` into
+    pub(super) fn parse_buffer(source: &str) -> Buffer {
+        let text = parse_canvas(source).unwrap();
+        let area = Rect::new(0, 0, text.width() as u16, text.height() as u16);
+        let mut buf = Buffer::empty(area);
+        text.render(area, &mut buf);
+        buf
+    }
+
+    #[test]
+    fn rejects_canvases_too_large_for_a_buffer() {
+        assert_eq!(
+            parse_canvas(&"x".repeat(70_000)),
+            Err(CanvasError::TooLarge { width: 70_000, height: 1 }),
+        );
+        assert_eq!(parse_canvas("a\nbc").map(|text| (text.width(), text.height())), Ok((2, 2)));
+    }
+
+    #[test]
+    fn ansi_round_trips_styles_and_wide_characters() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 2));
+        buf.set_string(0, 0, "a世界b", Style::new().fg(Color::Rgb(250, 189, 47)).bg(Color::Indexed(236)));
+        buf.set_string(6, 0, "🦀", Style::new().add_modifier(Modifier::BOLD | Modifier::REVERSED));
+        buf.set_string(0, 1, "ｆｕｌｌ", Style::new().fg(Color::LightCyan).add_modifier(Modifier::ITALIC));
+
+        let ansi = buffer_to_ansi(&buf);
+        assert_eq!(ansi.lines().count(), 2);
+        assert_eq!(parse_buffer(&ansi), buf);
+    }
+}
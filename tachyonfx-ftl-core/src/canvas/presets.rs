@@ -0,0 +1,326 @@
+//! Built-in canvases, for trying out effects without an ANSI file at hand.
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Constraint, Layout, Margin, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, BorderType, Clear, Gauge, List, Padding, Paragraph, Row, Sparkline, Table, Widget, Wrap};
+use tachyonfx::{color_from_hsl, Interpolatable};
+use crate::canvas::buffer_to_ansi;
+use crate::gruvbox::Gruvbox;
+
+/// A named canvas, drawn on demand.
+#[derive(Clone, Copy)]
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    draw: fn() -> Buffer,
+}
+
+impl Preset {
+    pub fn buffer(&self) -> Buffer {
+        (self.draw)()
+    }
+
+    /// the canvas as ANSI-escaped text, as sent with `AppEvent::UpdateCanvas`
+    pub fn source(&self) -> String {
+        buffer_to_ansi(&self.buffer())
+    }
+}
+
+pub const PRESETS: &[Preset] = &[
+    Preset { name: "logo",      description: "tachyonfx in block letters", draw: logo },
+    Preset { name: "dashboard", description: "gauges, a sparkline, a list and a log", draw: dashboard },
+    Preset { name: "table",     description: "a table with a header and a selected row", draw: table },
+    Preset { name: "dialog",    description: "a confirmation dialog over an app", draw: dialog },
+    Preset { name: "gradient",  description: "a grid of color swatches", draw: gradient_grid },
+    Preset { name: "unicode",   description: "wide characters: CJK, emoji and fullwidth forms", draw: wide_unicode },
+];
+
+pub fn find(name: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|p| p.name == name)
+}
+
+fn canvas(width: u16, height: u16) -> Buffer {
+    let area = Rect::new(0, 0, width, height);
+    let mut buf = Buffer::empty(area);
+    buf.set_style(area, Style::new().fg(Gruvbox::light2()).bg(Gruvbox::dark0_hard()));
+    buf
+}
+
+fn panel(title: &str) -> Block<'_> {
+    Block::bordered()
+        .border_type(BorderType::Rounded)
+        .border_style(Style::new().fg(Gruvbox::dark4()))
+        .title(Span::styled(format!(" {title} "), Style::new().fg(Gruvbox::yellow_bright())))
+}
+
+fn logo() -> Buffer {
+    const GLYPHS: [(char, [&str; 5]); 9] = [
+        ('t', ["█████", "  █  ", "  █  ", "  █  ", "  █  "]),
+        ('a', [" ███ ", "█   █", "█████", "█   █", "█   █"]),
+        ('c', [" ████", "█    ", "█    ", "█    ", " ████"]),
+        ('h', ["█   █", "█   █", "█████", "█   █", "█   █"]),
+        ('y', ["█   █", " █ █ ", "  █  ", "  █  ", "  █  "]),
+        ('o', [" ███ ", "█   █", "█   █", "█   █", " ███ "]),
+        ('n', ["█   █", "██  █", "█ █ █", "█  ██", "█   █"]),
+        ('f', ["█████", "█    ", "████ ", "█    ", "█    "]),
+        ('x', ["█   █", " █ █ ", "  █  ", " █ █ ", "█   █"]),
+    ];
+
+    let mut buf = canvas(61, 10);
+    let glyph = |c: char| GLYPHS.iter().find(|(g, _)| *g == c).map(|(_, rows)| rows);
+    let rows: Vec<String> = (0..5)
+        .map(|row| "tachyonfx".chars()
+            .filter_map(glyph)
+            .map(|g| g[row])
+            .collect::<Vec<_>>()
+            .join(" "))
+        .collect();
+
+    let width = rows[0].chars().count() as f32;
+    for (y, row) in rows.iter().enumerate() {
+        for (x, c) in row.chars().enumerate().filter(|(_, c)| *c != ' ') {
+            let color = Gruvbox::orange_bright().lerp(&Gruvbox::purple_bright(), x as f32 / width);
+            buf[(3 + x as u16, 2 + y as u16)]
+                .set_char(c)
+                .set_fg(color);
+        }
+    }
+
+    Line::from("effects and animations for ratatui")
+        .style(Style::new().fg(Gruvbox::gray245()).add_modifier(Modifier::ITALIC))
+        .alignment(Alignment::Center)
+        .render(Rect::new(0, 8, 61, 1), &mut buf);
+
+    buf
+}
+
+fn dashboard() -> Buffer {
+    let mut buf = canvas(64, 20);
+    let area = buf.area;
+
+    let outer = panel("dashboard");
+    let inner = outer.inner(area);
+    outer.render(area, &mut buf);
+
+    let [gauges, middle, log] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(6),
+        Constraint::Length(5),
+    ]).areas(inner);
+
+    let gauge_areas: [Rect; 3] = Layout::horizontal([Constraint::Ratio(1, 3); 3]).areas(gauges);
+    let gauge_values = [("cpu", 0.42, Gruvbox::green_bright()), ("mem", 0.71, Gruvbox::yellow_bright()), ("disk", 0.93, Gruvbox::red_bright())];
+    for (area, (title, ratio, color)) in gauge_areas.into_iter().zip(gauge_values) {
+        Gauge::default()
+            .block(panel(title))
+            .gauge_style(Style::new().fg(color).bg(Gruvbox::dark1()))
+            .ratio(ratio)
+            .render(area, &mut buf);
+    }
+
+    let [requests, services] = Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .areas(middle);
+
+    Sparkline::default()
+        .block(panel("requests/s"))
+        .style(Style::new().fg(Gruvbox::aqua_bright()))
+        .data([3, 5, 4, 7, 9, 8, 12, 10, 14, 11, 9, 13, 16, 15, 12, 17, 19, 14, 11, 15, 18, 20, 17, 13, 16, 19, 22, 18, 15, 12, 14, 17, 21])
+        .render(requests, &mut buf);
+
+    let service = |name: &str, up: bool| {
+        let (status, color) = match up {
+            true  => ("● up  ", Gruvbox::green_bright()),
+            false => ("● down", Gruvbox::red_bright()),
+        };
+        Line::from(vec![
+            Span::styled(status, Style::new().fg(color)),
+            Span::raw(format!(" {name}")),
+        ])
+    };
+    List::new([
+        service("api-gateway", true),
+        service("auth", true),
+        service("billing", false),
+        service("search", true),
+        service("notifications", true),
+    ])
+        .block(panel("services"))
+        .render(services, &mut buf);
+
+    let entry = |time: &str, level: &str, color: Color, message: &str| Line::from(vec![
+        Span::styled(format!("{time} "), Style::new().fg(Gruvbox::gray245())),
+        Span::styled(format!("{level:5} "), Style::new().fg(color)),
+        Span::raw(message.to_string()),
+    ]);
+    Paragraph::new(vec![
+        entry("12:04:17", "INFO", Gruvbox::blue_bright(), "deployed api-gateway v2.14.1"),
+        entry("12:04:52", "WARN", Gruvbox::yellow_bright(), "billing: connection pool exhausted"),
+        entry("12:05:03", "ERROR", Gruvbox::red_bright(), "billing: health check failed"),
+    ])
+        .block(panel("log"))
+        .render(log, &mut buf);
+
+    buf
+}
+
+fn table() -> Buffer {
+    let mut buf = canvas(56, 12);
+    let area = buf.area;
+
+    let header = Row::new(["service", "status", "latency", "region"])
+        .style(Style::new().fg(Gruvbox::yellow_bright()).add_modifier(Modifier::BOLD))
+        .bottom_margin(1);
+
+    let row = |name: &'static str, up: bool, latency: &'static str, region: &'static str| {
+        let status = match up {
+            true  => Span::styled("healthy", Style::new().fg(Gruvbox::green_bright())),
+            false => Span::styled("failing", Style::new().fg(Gruvbox::red_bright())),
+        };
+        Row::new(vec![
+            Line::from(name),
+            Line::from(status),
+            Line::from(latency).alignment(Alignment::Right),
+            Line::from(region),
+        ])
+    };
+
+    let rows = [
+        row("api-gateway", true, "12ms", "eu-north-1"),
+        row("auth", true, "8ms", "eu-north-1"),
+        row("billing", false, "1204ms", "us-east-2").style(Style::new().bg(Gruvbox::dark2())),
+        row("search", true, "43ms", "us-east-2"),
+        row("notifications", true, "27ms", "ap-south-1"),
+        row("storage", true, "5ms", "ap-south-1"),
+    ];
+
+    Table::new(rows, [Constraint::Length(14), Constraint::Length(8), Constraint::Length(8), Constraint::Min(10)])
+        .header(header)
+        .column_spacing(2)
+        .block(panel("services").padding(Padding::horizontal(1)))
+        .render(area, &mut buf);
+
+    buf
+}
+
+fn dialog() -> Buffer {
+    let mut buf = canvas(56, 16);
+    let area = buf.area;
+
+    // the app behind the dialog
+    let files = ["README.md", "Cargo.toml", "notes.md", "src/main.rs", "src/app.rs", "src/ui.rs", "assets/", "target/"];
+    List::new(files.map(|f| Line::from(format!("  {f}"))))
+        .style(Style::new().fg(Gruvbox::dark4()))
+        .block(panel("files"))
+        .render(area, &mut buf);
+
+    let popup = area.inner(Margin::new(9, 4));
+    Clear.render(popup, &mut buf);
+
+    let block = Block::bordered()
+        .border_type(BorderType::Double)
+        .border_style(Style::new().fg(Gruvbox::red_bright()))
+        .title(Line::from(" Delete file? ").alignment(Alignment::Center))
+        .style(Style::new().fg(Gruvbox::light1()).bg(Gruvbox::dark1()))
+        .padding(Padding::horizontal(1));
+    let inner = block.inner(popup);
+    block.render(popup, &mut buf);
+
+    let [message, buttons] = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(inner);
+    Paragraph::new(Text::from(vec![
+        Line::from(vec![
+            Span::raw("This permanently deletes "),
+            Span::styled("notes.md", Style::new().fg(Gruvbox::yellow_bright())),
+            Span::raw("."),
+        ]),
+        Line::from("It can't be undone."),
+    ]))
+        .wrap(Wrap { trim: true })
+        .render(message, &mut buf);
+
+    Line::from(vec![
+        Span::styled("[ Cancel ]", Style::new().fg(Gruvbox::light3())),
+        Span::raw("  "),
+        Span::styled("[ Delete ]", Style::new().fg(Gruvbox::light0()).bg(Gruvbox::red()).add_modifier(Modifier::BOLD)),
+    ])
+        .alignment(Alignment::Right)
+        .render(buttons, &mut buf);
+
+    buf
+}
+
+fn gradient_grid() -> Buffer {
+    const COLUMNS: u16 = 12;
+    const ROWS: u16 = 8;
+    const SWATCH: (u16, u16) = (4, 2);
+
+    let mut buf = canvas(COLUMNS * SWATCH.0, ROWS * SWATCH.1);
+    for row in 0..ROWS {
+        for column in 0..COLUMNS {
+            let hue = column as f32 / COLUMNS as f32 * 360.0;
+            let lightness = 75.0 - row as f32 / (ROWS - 1) as f32 * 55.0;
+            let color = color_from_hsl(hue, 80.0, lightness);
+
+            let swatch = Rect::new(column * SWATCH.0, row * SWATCH.1, SWATCH.0, SWATCH.1);
+            buf.set_style(swatch, Style::new().bg(color));
+        }
+    }
+
+    buf
+}
+
+fn wide_unicode() -> Buffer {
+    let mut buf = canvas(40, 10);
+    let area = buf.area;
+
+    let sample = |label: &str, text: &str, color: Color| Line::from(vec![
+        Span::styled(format!("{label:>10} "), Style::new().fg(Gruvbox::gray245())),
+        Span::styled(text.to_string(), Style::new().fg(color)),
+    ]);
+
+    Paragraph::new(vec![
+        sample("japanese", "こんにちは、世界", Gruvbox::red_bright()),
+        sample("chinese", "你好，世界", Gruvbox::orange_bright()),
+        sample("korean", "안녕하세요 세계", Gruvbox::yellow_bright()),
+        sample("emoji", "🚀 ✨ 🎨 🔥 🌈", Gruvbox::green_bright()),
+        sample("fullwidth", "ＡＢＣ１２３", Gruvbox::aqua_bright()),
+        sample("mixed", "a漢b字c→◆←", Gruvbox::blue_bright()),
+        sample("blocks", "▁▂▃▄▅▆▇█ ░▒▓", Gruvbox::purple_bright()),
+        sample("braille", "⣿⣷⣯⣟⡿⢿⣻⣽⣾", Gruvbox::light1()),
+    ])
+        .block(panel("wide unicode"))
+        .render(area, &mut buf);
+
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::canvas::tests::parse_buffer;
+    use super::*;
+
+    #[test]
+    fn presets_round_trip_through_ansi() {
+        for preset in PRESETS {
+            let buffer = preset.buffer();
+            assert!(buffer.area.width > 0 && buffer.area.height > 0, "{}", preset.name);
+            // something other than the blank canvas; the gradient has no text
+            let blank = canvas(buffer.area.width, buffer.area.height);
+            assert_ne!(buffer, blank, "{}", preset.name);
+
+            // cells covered by wide characters aren't part of the text, and
+            // are reset when it's parsed; the visible cells must match
+            let source = preset.source();
+            let parsed = parse_buffer(&source);
+            assert_eq!(parsed.area, buffer.area, "{}", preset.name);
+            assert_eq!(buffer_to_ansi(&parsed), source, "{}", preset.name);
+        }
+    }
+
+    #[test]
+    fn finds_presets_by_name() {
+        assert_eq!(find("unicode").map(|p| p.name), Some("unicode"));
+        assert!(find("nope").is_none());
+    }
+}
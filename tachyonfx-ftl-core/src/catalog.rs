@@ -0,0 +1,570 @@
+//! The vocabulary of the effect DSL: the effects registered with `EffectDsl`,
+//! the methods that can be chained onto values and the values arguments take,
+//! each with its signature and a short description.
+//!
+//! tachyonfx doesn't expose the names known to its compiler, so the catalogue
+//! is maintained by hand; it follows the compilers and literal promotion rules
+//! of tachyonfx 0.15.
+
+use std::fmt;
+use std::ops::Range;
+use serde::Serialize;
+use crate::lexer::{tokenize, Token, TokenKind};
+
+/// What a [Symbol] is; decides how it's written and completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    /// an effect constructor, e.g. `fx::dissolve`
+    Effect,
+    /// a method chained onto a value, e.g. `.with_filter`
+    Method,
+    /// an associated function or tuple variant, e.g. `Color::Rgb`
+    Function,
+    /// an enum variant or constant, e.g. `Interpolation::QuadOut`
+    Variant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Param {
+    pub name: &'static str,
+    pub ty: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol {
+    pub kind: SymbolKind,
+    /// the type the symbol belongs to, e.g. `Interpolation`; `fx` for effects
+    pub namespace: &'static str,
+    pub name: &'static str,
+    /// parameters of callable symbols; `None` for variants
+    pub params: Option<&'static [Param]>,
+    pub doc: &'static str,
+}
+
+impl Symbol {
+    /// the symbol as written in the DSL, e.g. `fx::dissolve` or `.with_filter`
+    pub fn path(&self) -> String {
+        match self.kind {
+            SymbolKind::Method => format!(".{}", self.name),
+            _                  => format!("{}::{}", self.namespace, self.name),
+        }
+    }
+
+    /// Whether the namespace may be left out, as in `dissolve(...)` or
+    /// `QuadOut`; the compiler resolves these names on its own.
+    pub fn is_unqualified_ok(&self) -> bool {
+        match self.kind {
+            SymbolKind::Effect   => true,
+            SymbolKind::Method   => false,
+            SymbolKind::Function => matches!(self.namespace, "CellFilter" | "Constraint"),
+            SymbolKind::Variant  => true,
+        }
+    }
+
+    /// the path followed by the parameters, e.g. `fx::dissolve(timer: EffectTimer)`
+    pub fn signature(&self) -> String {
+        match self.params {
+            Some(params) => format!("{}({})", self.path(), params.iter()
+                .map(|p| format!("{}: {}", p.name, p.ty))
+                .collect::<Vec<_>>()
+                .join(", ")),
+            None => self.path(),
+        }
+    }
+
+    /// the text inserted on completion, with or without the namespace; calls
+    /// are opened, or closed when there's nothing to pass
+    pub fn insert_text(&self, qualified: bool) -> String {
+        let path = match qualified {
+            true  => self.path(),
+            false => self.name.to_string(),
+        };
+
+        match self.params {
+            Some([]) => format!("{path}()"),
+            Some(_)  => format!("{path}("),
+            None     => path,
+        }
+    }
+
+    /// Whether `path`, as written in the DSL, refers to this symbol.
+    pub fn matches(&self, path: &str) -> bool {
+        match (self.kind, path.strip_prefix('.')) {
+            (SymbolKind::Method, Some(name)) => name == self.name,
+            (SymbolKind::Method, None)       => false,
+            (_, Some(_))                     => false,
+            _ => match path.rsplit_once("::") {
+                Some((namespace, name)) => namespace == self.namespace && name == self.name,
+                None                    => path == self.name && self.is_unqualified_ok(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.signature())
+    }
+}
+
+/// The catalogue serialized for editors: `{"symbols": [...]}`, each symbol
+/// with its path, signature and the text to insert on completion, qualified
+/// and, where the namespace may be left out, unqualified.
+pub fn catalog_json() -> String {
+    let symbols = SYMBOLS.iter()
+        .map(|s| serde_json::json!({
+            "kind": s.kind,
+            "namespace": s.namespace,
+            "name": s.name,
+            "path": s.path(),
+            "signature": s.signature(),
+            "params": s.params,
+            "doc": s.doc,
+            "insert_text": s.insert_text(true),
+            "unqualified_insert_text": s.is_unqualified_ok().then(|| s.insert_text(false)),
+        }))
+        .collect::<Vec<_>>();
+
+    serde_json::json!({ "symbols": symbols }).to_string()
+}
+
+/// the symbol `path` refers to, e.g. `fx::dissolve`, `QuadOut` or `.with_filter`
+pub fn find(path: &str) -> Option<&'static Symbol> {
+    SYMBOLS.iter().find(|s| s.matches(path))
+}
+
+/// A call surrounding a position in the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallContext {
+    pub symbol: &'static Symbol,
+    /// index of the argument the position is in
+    pub active_param: usize,
+}
+
+/// The symbol at byte offset `offset` of `source`, along with the byte range
+/// of its path.
+pub fn symbol_at(source: &str, offset: usize) -> Option<(&'static Symbol, Range<usize>)> {
+    let tokens = tokenize(source);
+    let i = tokens.iter().position(|t| t.kind == TokenKind::Ident && t.start <= offset && offset <= t.end())?;
+
+    // the path extends over `::` in both directions; methods are preceded by `.`
+    let mut first = i;
+    while first >= 3 && is_path_separator(&tokens[first - 2..first]) && tokens[first - 3].kind == TokenKind::Ident {
+        first -= 3;
+    }
+    let mut last = i;
+    while last + 3 < tokens.len() && is_path_separator(&tokens[last + 1..last + 3]) && tokens[last + 3].kind == TokenKind::Ident {
+        last += 3;
+    }
+
+    let is_method = first > 0 && tokens[first - 1].kind == TokenKind::Punct('.');
+    let start = if is_method { tokens[first - 1].start } else { tokens[first].start };
+    let range = start..tokens[last].end();
+
+    find(&source[range.clone()]).map(|symbol| (symbol, range))
+}
+
+/// The innermost call of a known function around byte offset `offset`, e.g.
+/// `fx::fade_to_fg` when the offset is within `fx::fade_to_fg(Color::Red, |)`.
+/// Tuples and arrays within the call are skipped, so a position inside the
+/// `(500, QuadOut)` timer is still reported as the timer argument.
+pub fn call_at(source: &str, offset: usize) -> Option<CallContext> {
+    let tokens = tokenize(source);
+
+    // each open bracket before the offset, with the number of commas at its level
+    let mut open: Vec<(usize, usize)> = Vec::new();
+    for (i, token) in tokens.iter().enumerate().take_while(|(_, t)| t.start < offset) {
+        match token.kind {
+            TokenKind::Open(_)    => open.push((i, 0)),
+            TokenKind::Close(_)   => { open.pop(); }
+            TokenKind::Punct(',') => if let Some((_, commas)) = open.last_mut() { *commas += 1 },
+            _                     => {}
+        }
+    }
+
+    open.iter().rev()
+        .filter(|&&(i, _)| tokens[i].kind == TokenKind::Open('('))
+        .find_map(|&(i, commas)| {
+            let path = path_before(source, &tokens[..i])?;
+            let symbol = find(path).filter(|s| s.params.is_some())?;
+            Some(CallContext { symbol, active_param: commas })
+        })
+}
+
+/// The path being typed at byte offset `offset`, e.g. `Interpolation::Qu` or
+/// `.with_`, and where it starts; empty when the offset isn't after a path.
+pub fn partial_path_at(source: &str, offset: usize) -> (usize, &str) {
+    let before = &source[..offset];
+    let start = before
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+        .map(|i| i + before[i..].chars().next().map_or(1, char::len_utf8))
+        .unwrap_or(0);
+
+    // methods keep their leading dot
+    let start = match before[..start].ends_with('.') && !before[start..].contains("::") {
+        true  => start - 1,
+        false => start,
+    };
+
+    (start, &before[start..])
+}
+
+/// A symbol completing a partial path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub symbol: &'static Symbol,
+    /// replaces the partial path
+    pub text: String,
+}
+
+/// The symbols that complete `partial`, as returned by [partial_path_at].
+/// Names that don't need a namespace complete without one, unless it's being
+/// typed.
+pub fn completions(partial: &str) -> Vec<Completion> {
+    let completion = |symbol: &'static Symbol, qualified: bool| Completion {
+        symbol,
+        text: symbol.insert_text(qualified),
+    };
+
+    SYMBOLS.iter()
+        .filter_map(|s| match (s.kind, partial.strip_prefix('.')) {
+            (SymbolKind::Method, Some(name)) => s.name.starts_with(name).then(|| completion(s, true)),
+            (SymbolKind::Method, None) | (_, Some(_)) => None,
+            _ => match partial.rsplit_once("::") {
+                Some((namespace, name)) => (s.namespace == namespace && s.name.starts_with(name))
+                    .then(|| completion(s, true)),
+                None if s.is_unqualified_ok() && s.name.starts_with(partial) => Some(completion(s, false)),
+                None => s.namespace.starts_with(partial).then(|| completion(s, true)),
+            },
+        })
+        .collect()
+}
+
+fn is_path_separator(tokens: &[Token]) -> bool {
+    matches!(tokens, [a, b] if a.kind == TokenKind::Punct(':') && b.kind == TokenKind::Punct(':'))
+}
+
+/// the path ending with the last of `tokens`, e.g. `fx::dissolve` or `.fg`
+fn path_before<'a>(source: &'a str, tokens: &[Token]) -> Option<&'a str> {
+    let last = tokens.len().checked_sub(1)?;
+    if tokens[last].kind != TokenKind::Ident {
+        return None;
+    }
+
+    let mut first = last;
+    while first >= 3 && is_path_separator(&tokens[first - 2..first]) && tokens[first - 3].kind == TokenKind::Ident {
+        first -= 3;
+    }
+    let start = match first > 0 && tokens[first - 1].kind == TokenKind::Punct('.') {
+        true  => tokens[first - 1].start,
+        false => tokens[first].start,
+    };
+
+    Some(&source[start..tokens[last].end()])
+}
+
+const fn param(name: &'static str, ty: &'static str) -> Param {
+    Param { name, ty }
+}
+
+const fn effect(name: &'static str, params: &'static [Param], doc: &'static str) -> Symbol {
+    Symbol { kind: SymbolKind::Effect, namespace: "fx", name, params: Some(params), doc }
+}
+
+const fn method(
+    namespace: &'static str,
+    name: &'static str,
+    params: &'static [Param],
+    doc: &'static str,
+) -> Symbol {
+    Symbol { kind: SymbolKind::Method, namespace, name, params: Some(params), doc }
+}
+
+const fn function(
+    namespace: &'static str,
+    name: &'static str,
+    params: &'static [Param],
+    doc: &'static str,
+) -> Symbol {
+    Symbol { kind: SymbolKind::Function, namespace, name, params: Some(params), doc }
+}
+
+const fn variant(namespace: &'static str, name: &'static str, doc: &'static str) -> Symbol {
+    Symbol { kind: SymbolKind::Variant, namespace, name, params: None, doc }
+}
+
+const TIMER: Param = param("timer", "EffectTimer");
+const EFFECT: Param = param("effect", "Effect");
+const EFFECTS: Param = param("effects", "&[Effect]");
+const MOTION_ARGS: [Param; 5] = [
+    param("direction", "Motion"),
+    param("gradient_length", "u16"),
+    param("randomness", "u16"),
+    param("color", "Color"),
+    TIMER,
+];
+
+/// Every symbol known to the DSL. Kept by hand, as tachyonfx has no API listing
+/// what it understands; the tests check the effects against the names registered
+/// with `EffectDsl`, which it only shows through `Debug`.
+pub static SYMBOLS: &[Symbol] = &[
+    // effects
+    effect("parallel", &[EFFECTS], "Runs the effects at the same time; completes when all of them are done."),
+    effect("sequence", &[EFFECTS], "Runs the effects one after another."),
+    effect("coalesce", &[TIMER], "Reforms dissolved cells into their original content; the reverse of dissolve."),
+    effect("coalesce_from", &[param("style", "Style"), TIMER], "Reforms cells from the given style into their original content."),
+    effect("consume_tick", &[], "Completes after consuming a single tick."),
+    effect("delay", &[param("duration", "EffectTimer"), EFFECT], "Waits for the duration before starting the effect."),
+    effect("dissolve", &[TIMER], "Dissolves the cells into empty space, at random."),
+    effect("dissolve_to", &[param("style", "Style"), TIMER], "Dissolves the cells into the given style, at random."),
+    effect("explode", &[param("force", "f32"), param("force_rng_factor", "f32"), TIMER], "Moves the cells outward from the center of the area, like debris from an explosion."),
+    effect("fade_from", &[param("fg", "Color"), param("bg", "Color"), TIMER], "Fades the foreground and background from the given colors to the original colors."),
+    effect("fade_from_fg", &[param("fg", "Color"), TIMER], "Fades the foreground from the given color to the original color."),
+    effect("fade_to", &[param("fg", "Color"), param("bg", "Color"), TIMER], "Fades the foreground and background to the given colors."),
+    effect("fade_to_fg", &[param("fg", "Color"), TIMER], "Fades the foreground to the given color."),
+    effect("freeze_at", &[param("alpha", "f32"), param("set_raw_alpha", "bool"), EFFECT], "Shows the effect frozen at the given alpha, from 0.0 to 1.0; a raw alpha bypasses the interpolation."),
+    effect("hsl_shift", &[param("hsl_fg_change", "Option<[f32; 3]>"), param("hsl_bg_change", "Option<[f32; 3]>"), TIMER], "Shifts the hue, saturation and lightness of the foreground and background colors."),
+    effect("hsl_shift_fg", &[param("hsl_fg_change", "[f32; 3]"), TIMER], "Shifts the hue, saturation and lightness of the foreground color."),
+    effect("never_complete", &[EFFECT], "Keeps the effect running after it has finished."),
+    effect("ping_pong", &[EFFECT], "Plays the effect forwards, then backwards."),
+    effect("prolong_end", &[param("duration", "EffectTimer"), EFFECT], "Extends the effect by the duration after it ends, holding its final state."),
+    effect("prolong_start", &[param("duration", "EffectTimer"), EFFECT], "Extends the effect by the duration before it starts, holding its initial state."),
+    effect("remap_alpha", &[param("alpha_start", "f32"), param("alpha_end", "f32"), EFFECT], "Plays only the part of the effect between the two alpha values, from 0.0 to 1.0."),
+    effect("repeat", &[EFFECT, param("mode", "RepeatMode")], "Repeats the effect forever, a number of times or for a duration."),
+    effect("repeating", &[EFFECT], "Repeats the effect forever."),
+    effect("sleep", &[param("duration", "EffectTimer")], "Does nothing for the duration."),
+    effect("slide_in", &MOTION_ARGS, "Slides the content in from the given direction, revealing it from the color."),
+    effect("slide_out", &MOTION_ARGS, "Slides the content out in the given direction, leaving the color behind."),
+    effect("sweep_in", &MOTION_ARGS, "Sweeps the content in from the color, with a gradient along the given direction."),
+    effect("sweep_out", &MOTION_ARGS, "Sweeps the content out to the color, with a gradient along the given direction."),
+    effect("term256_colors", &[], "Downsamples all colors to the 256-color palette."),
+    effect("timed_never_complete", &[param("duration", "Duration"), EFFECT], "Keeps the effect running until the duration has passed."),
+    effect("with_duration", &[param("duration", "Duration"), EFFECT], "Stops the effect once the duration has passed."),
+
+    // methods
+    method("Effect", "with_filter", &[param("filter", "CellFilter")], "Applies the effect only to the cells matching the filter."),
+    method("Effect", "with_area", &[param("area", "Rect")], "Applies the effect only to the area."),
+    method("Effect", "with_color_space", &[param("color_space", "ColorSpace")], "Sets the color space colors are interpolated in."),
+    method("Effect", "with_duration", &[param("duration", "Duration")], "Stops the effect once the duration has passed."),
+    method("Effect", "reversed", &[], "Plays the effect backwards."),
+    method("Style", "fg", &[param("color", "Color")], "Sets the foreground color."),
+    method("Style", "bg", &[param("color", "Color")], "Sets the background color."),
+    method("Style", "add_modifier", &[param("modifier", "Modifier")], "Adds the modifier."),
+    method("Style", "remove_modifier", &[param("modifier", "Modifier")], "Removes the modifier."),
+    method("Layout", "constraints", &[param("constraints", "&[Constraint]")], "Sets the constraints of the layout's segments."),
+    method("Layout", "margin", &[param("margin", "u16")], "Sets the margin on all sides."),
+    method("Layout", "horizontal_margin", &[param("margin", "u16")], "Sets the left and right margin."),
+    method("Layout", "vertical_margin", &[param("margin", "u16")], "Sets the top and bottom margin."),
+    method("Layout", "spacing", &[param("spacing", "u16")], "Sets the space between segments."),
+    method("Rect", "clamp", &[param("other", "Rect")], "Moves and shrinks the rect to fit within the other."),
+    method("Rect", "inner", &[param("margin", "Margin")], "Shrinks the rect by the margin."),
+    method("Rect", "intersection", &[param("other", "Rect")], "The area covered by both rects."),
+    method("Rect", "union", &[param("other", "Rect")], "The smallest rect covering both rects."),
+    method("Rect", "offset", &[param("offset", "Offset")], "Moves the rect by the offset."),
+
+    // timers and durations
+    function("EffectTimer", "from_ms", &[param("duration", "u32"), param("interpolation", "Interpolation")], "A timer of the duration in milliseconds; `(ms, interpolation)` is the short form."),
+    function("EffectTimer", "new", &[param("duration", "Duration"), param("interpolation", "Interpolation")], "A timer of the duration."),
+    function("Duration", "from_millis", &[param("ms", "u32")], "A duration in milliseconds; a plain number is the short form."),
+    function("Duration", "from_secs_f32", &[param("seconds", "f32")], "A duration in seconds."),
+    function("RepeatMode", "Times", &[param("n", "u32")], "Repeats the effect n times."),
+    function("RepeatMode", "Duration", &[param("duration", "Duration")], "Repeats the effect for the duration."),
+    variant("RepeatMode", "Forever", "Repeats the effect forever."),
+
+    // interpolation
+    variant("Interpolation", "Linear", "Constant speed."),
+    variant("Interpolation", "Reverse", "Constant speed, from end to start."),
+    variant("Interpolation", "BackIn", "Pulls back slightly before accelerating."),
+    variant("Interpolation", "BackOut", "Overshoots slightly before settling."),
+    variant("Interpolation", "BackInOut", "Pulls back at the start and overshoots at the end."),
+    variant("Interpolation", "BounceIn", "Bounces at the start."),
+    variant("Interpolation", "BounceOut", "Bounces at the end."),
+    variant("Interpolation", "BounceInOut", "Bounces at the start and the end."),
+    variant("Interpolation", "CircIn", "Circular easing; accelerates from zero."),
+    variant("Interpolation", "CircOut", "Circular easing; decelerates to zero."),
+    variant("Interpolation", "CircInOut", "Circular easing; accelerates, then decelerates."),
+    variant("Interpolation", "CubicIn", "Cubic easing; accelerates from zero."),
+    variant("Interpolation", "CubicOut", "Cubic easing; decelerates to zero."),
+    variant("Interpolation", "CubicInOut", "Cubic easing; accelerates, then decelerates."),
+    variant("Interpolation", "ElasticIn", "Oscillates with growing amplitude at the start."),
+    variant("Interpolation", "ElasticOut", "Oscillates with decaying amplitude at the end."),
+    variant("Interpolation", "ElasticInOut", "Oscillates at the start and the end."),
+    variant("Interpolation", "ExpoIn", "Exponential easing; accelerates from zero."),
+    variant("Interpolation", "ExpoOut", "Exponential easing; decelerates to zero."),
+    variant("Interpolation", "ExpoInOut", "Exponential easing; accelerates, then decelerates."),
+    variant("Interpolation", "QuadIn", "Quadratic easing; accelerates from zero."),
+    variant("Interpolation", "QuadOut", "Quadratic easing; decelerates to zero."),
+    variant("Interpolation", "QuadInOut", "Quadratic easing; accelerates, then decelerates."),
+    variant("Interpolation", "QuartIn", "Quartic easing; accelerates from zero."),
+    variant("Interpolation", "QuartOut", "Quartic easing; decelerates to zero."),
+    variant("Interpolation", "QuartInOut", "Quartic easing; accelerates, then decelerates."),
+    variant("Interpolation", "QuintIn", "Quintic easing; accelerates from zero."),
+    variant("Interpolation", "QuintOut", "Quintic easing; decelerates to zero."),
+    variant("Interpolation", "QuintInOut", "Quintic easing; accelerates, then decelerates."),
+    variant("Interpolation", "SineIn", "Sinusoidal easing; accelerates from zero."),
+    variant("Interpolation", "SineOut", "Sinusoidal easing; decelerates to zero."),
+    variant("Interpolation", "SineInOut", "Sinusoidal easing; accelerates, then decelerates."),
+
+    // cell filters
+    variant("CellFilter", "All", "Every cell."),
+    variant("CellFilter", "Text", "Cells with a visible symbol."),
+    function("CellFilter", "Area", &[param("area", "Rect")], "Cells within the area."),
+    function("CellFilter", "FgColor", &[param("color", "Color")], "Cells with the foreground color."),
+    function("CellFilter", "BgColor", &[param("color", "Color")], "Cells with the background color."),
+    function("CellFilter", "Inner", &[param("margin", "Margin")], "Cells inside the margin."),
+    function("CellFilter", "Outer", &[param("margin", "Margin")], "Cells outside the margin."),
+    function("CellFilter", "AllOf", &[param("filters", "Vec<CellFilter>")], "Cells matching all of the filters."),
+    function("CellFilter", "AnyOf", &[param("filters", "Vec<CellFilter>")], "Cells matching any of the filters."),
+    function("CellFilter", "NoneOf", &[param("filters", "Vec<CellFilter>")], "Cells matching none of the filters."),
+    function("CellFilter", "Not", &[param("filter", "Box<CellFilter>")], "Cells not matching the filter."),
+    function("CellFilter", "Layout", &[param("layout", "Layout"), param("index", "u16")], "Cells within the layout segment at the index."),
+    function("CellFilter", "PositionFn", &[param("f", "var")], "Cells accepted by a function bound to a variable by the host."),
+    function("CellFilter", "EvalCell", &[param("f", "var")], "Cells accepted by a function bound to a variable by the host."),
+
+    // motion and direction
+    variant("Motion", "LeftToRight", "From the left edge to the right edge."),
+    variant("Motion", "RightToLeft", "From the right edge to the left edge."),
+    variant("Motion", "UpToDown", "From the top edge to the bottom edge."),
+    variant("Motion", "DownToUp", "From the bottom edge to the top edge."),
+    variant("Direction", "Horizontal", "Segments are laid out side by side."),
+    variant("Direction", "Vertical", "Segments are laid out on top of each other."),
+
+    // colors
+    function("Color", "Rgb", &[param("r", "u8"), param("g", "u8"), param("b", "u8")], "A 24-bit color."),
+    function("Color", "Indexed", &[param("index", "u8")], "A color of the 256-color palette."),
+    function("Color", "from_u32", &[param("rgb", "u32")], "A 24-bit color from 0xRRGGBB."),
+    variant("Color", "Reset", "The terminal's default color."),
+    variant("Color", "Black", "ANSI color 0."),
+    variant("Color", "Red", "ANSI color 1."),
+    variant("Color", "Green", "ANSI color 2."),
+    variant("Color", "Yellow", "ANSI color 3."),
+    variant("Color", "Blue", "ANSI color 4."),
+    variant("Color", "Magenta", "ANSI color 5."),
+    variant("Color", "Cyan", "ANSI color 6."),
+    variant("Color", "Gray", "ANSI color 7."),
+    variant("Color", "DarkGray", "ANSI color 8."),
+    variant("Color", "LightRed", "ANSI color 9."),
+    variant("Color", "LightGreen", "ANSI color 10."),
+    variant("Color", "LightYellow", "ANSI color 11."),
+    variant("Color", "LightBlue", "ANSI color 12."),
+    variant("Color", "LightMagenta", "ANSI color 13."),
+    variant("Color", "LightCyan", "ANSI color 14."),
+    variant("Color", "White", "ANSI color 15."),
+    variant("ColorSpace", "Rgb", "Interpolates the red, green and blue channels."),
+    variant("ColorSpace", "Hsl", "Interpolates hue, saturation and lightness."),
+    variant("ColorSpace", "Hsv", "Interpolates hue, saturation and value."),
+
+    // styles
+    function("Style", "new", &[], "A style that changes nothing; add colors and modifiers with the methods."),
+    function("Style", "default", &[], "Same as Style::new()."),
+    variant("Modifier", "BOLD", "Bold text."),
+    variant("Modifier", "DIM", "Faint text."),
+    variant("Modifier", "ITALIC", "Italic text."),
+    variant("Modifier", "UNDERLINED", "Underlined text."),
+    variant("Modifier", "SLOW_BLINK", "Slowly blinking text."),
+    variant("Modifier", "RAPID_BLINK", "Rapidly blinking text."),
+    variant("Modifier", "REVERSED", "Swapped foreground and background colors."),
+    variant("Modifier", "HIDDEN", "Hidden text."),
+    variant("Modifier", "CROSSED_OUT", "Crossed out text."),
+
+    // areas and layouts
+    function("Rect", "new", &[param("x", "u16"), param("y", "u16"), param("width", "u16"), param("height", "u16")], "An area of the canvas."),
+    function("Margin", "new", &[param("horizontal", "u16"), param("vertical", "u16")], "A margin on the left and right, and on the top and bottom."),
+    function("Layout", "horizontal", &[param("constraints", "&[Constraint]")], "Splits an area into columns."),
+    function("Layout", "vertical", &[param("constraints", "&[Constraint]")], "Splits an area into rows."),
+    function("Layout", "new", &[param("direction", "Direction"), param("constraints", "&[Constraint]")], "Splits an area in the direction."),
+    function("Constraint", "Length", &[param("length", "u16")], "A segment of the exact length."),
+    function("Constraint", "Percentage", &[param("percentage", "u16")], "A segment of the percentage of the area."),
+    function("Constraint", "Min", &[param("min", "u16")], "A segment at least this long."),
+    function("Constraint", "Max", &[param("max", "u16")], "A segment at most this long."),
+    function("Constraint", "Fill", &[param("weight", "u16")], "A segment sharing the remaining space by weight."),
+    function("Constraint", "Ratio", &[param("numerator", "u32"), param("denominator", "u32")], "A segment of the ratio of the area."),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tachyonfx::dsl::EffectDsl;
+
+    /// a value of each parameter type in the catalogue
+    fn sample(ty: &str) -> &'static str {
+        match ty {
+            "EffectTimer"       => "(500, Interpolation::QuadOut)",
+            "Effect"            => "fx::dissolve(500)",
+            "&[Effect]"         => "&[fx::dissolve(500), fx::coalesce(500)]",
+            "Duration"          => "Duration::from_millis(500)",
+            "RepeatMode"        => "RepeatMode::Times(2)",
+            "Interpolation"     => "Interpolation::Linear",
+            "Style"             => "Style::new().fg(Color::Red)",
+            "Color"             => "Color::Rgb(255, 0, 0)",
+            "Motion"            => "Motion::LeftToRight",
+            "Rect"              => "Rect::new(0, 0, 10, 5)",
+            "Margin"            => "Margin::new(1, 1)",
+            "Layout"            => "Layout::horizontal(&[Constraint::Fill(1), Constraint::Length(4)])",
+            "CellFilter"        => "CellFilter::Text",
+            "Box<CellFilter>"   => "Box::new(CellFilter::Text)",
+            "Vec<CellFilter>"   => "vec![CellFilter::Text, CellFilter::All]",
+            "[f32; 3]"          => "[30.0, 0.0, 0.0]",
+            "Option<[f32; 3]>"  => "Some([30.0, 0.0, 0.0])",
+            "f32"               => "0.5",
+            "bool"              => "false",
+            "u8" | "u16" | "u32" => "2",
+            _ => panic!("no sample value for {ty}"),
+        }
+    }
+
+    /// the symbol called with a sample value for each parameter
+    fn sample_call(symbol: &Symbol) -> String {
+        match symbol.params {
+            Some(params) => format!("{}({})", symbol.path(), params.iter()
+                .map(|p| sample(p.ty))
+                .collect::<Vec<_>>()
+                .join(", ")),
+            None => symbol.path(),
+        }
+    }
+
+    #[test]
+    fn effects_interpolations_cell_filters_and_colors_compile() {
+        let failures: Vec<_> = SYMBOLS.iter()
+            // host-provided functions can't be written in the DSL
+            .filter(|s| !s.params.is_some_and(|params| params.iter().any(|p| p.ty == "var")))
+            .filter_map(|s| match (s.kind, s.namespace) {
+                (SymbolKind::Effect, _) => Some(sample_call(s)),
+                (_, "Interpolation")    => Some(format!("fx::dissolve((500, {}))", sample_call(s))),
+                (_, "CellFilter")       => Some(format!("fx::dissolve(500).with_filter({})", sample_call(s))),
+                (_, "Color")            => Some(format!("fx::fade_to_fg({}, 500)", sample_call(s))),
+                _                       => None,
+            })
+            .filter_map(|dsl| EffectDsl::new().compiler().compile(&dsl)
+                .err()
+                .map(|e| format!("{dsl}: {e}")))
+            .collect();
+
+        assert!(failures.is_empty(), "{failures:#?}");
+    }
+
+    #[test]
+    fn effects_match_the_names_registered_with_the_dsl() {
+        // tachyonfx only lists the registered names through `Debug`
+        let debug = format!("{:?}", EffectDsl::new());
+        let mut registered: Vec<_> = debug.split("name: \"")
+            .skip(1)
+            .filter_map(|s| s.split('"').next())
+            // parsed by the DSL itself, rather than registered
+            .chain(["parallel", "sequence"])
+            .collect();
+        registered.sort_unstable();
+        registered.dedup();
+
+        let mut effects: Vec<_> = SYMBOLS.iter()
+            .filter(|s| s.kind == SymbolKind::Effect)
+            .map(|s| s.name)
+            .collect();
+        effects.sort_unstable();
+
+        assert!(!registered.is_empty(), "{debug}");
+        assert_eq!(effects, registered);
+    }
+}
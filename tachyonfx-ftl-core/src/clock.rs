@@ -0,0 +1,115 @@
+//! Sources of frame time for [App](crate::App). The app asks its clock for the
+//! time passed since the previous frame; swapping the clock makes frame times
+//! reproducible for headless rendering, tests and recordings.
+
+use std::cell::Cell;
+use std::rc::Rc;
+use tachyonfx::Duration;
+
+#[cfg(not(feature = "web-backend"))]
+use std::time::Instant;
+#[cfg(feature = "web-backend")]
+use web_time::Instant;
+
+pub trait Clock {
+    /// time passed since the previous tick
+    fn tick(&mut self) -> Duration;
+}
+
+/// Measures wall clock time between ticks; the first tick measures from the
+/// creation of the clock.
+#[derive(Debug, Clone)]
+pub struct RealTimeClock {
+    last_tick: Instant,
+}
+
+impl Default for RealTimeClock {
+    fn default() -> Self {
+        Self { last_tick: Instant::now() }
+    }
+}
+
+impl Clock for RealTimeClock {
+    fn tick(&mut self) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_tick).into();
+        self.last_tick = now;
+        elapsed
+    }
+}
+
+/// Advances by the same step on every tick, regardless of the time passed.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedStepClock {
+    step: Duration,
+}
+
+impl FixedStepClock {
+    pub fn new(step: Duration) -> Self {
+        Self { step }
+    }
+}
+
+impl Clock for FixedStepClock {
+    fn tick(&mut self) -> Duration {
+        self.step
+    }
+}
+
+/// Advances only when told to. Clones share their time, so a clone kept after
+/// handing the clock to [App::set_clock](crate::App::set_clock) drives the app.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    pending: Rc<Cell<Duration>>,
+}
+
+impl ManualClock {
+    /// adds `delta` to the time reported by the next tick
+    pub fn advance(&self, delta: Duration) {
+        self.pending.set(self.pending.get() + delta);
+    }
+}
+
+impl Clock for ManualClock {
+    fn tick(&mut self) -> Duration {
+        self.pending.replace(Duration::ZERO)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_step_clock_returns_the_same_step_on_every_tick() {
+        let mut clock = FixedStepClock::new(Duration::from_millis(16));
+
+        let ticks: Vec<_> = (0..3).map(|_| clock.tick()).collect();
+        assert_eq!(ticks, [Duration::from_millis(16); 3]);
+    }
+
+    #[test]
+    fn manual_clock_reports_the_time_advanced_since_the_last_tick() {
+        let mut clock = ManualClock::default();
+        assert_eq!(clock.tick(), Duration::ZERO);
+
+        clock.advance(Duration::from_millis(10));
+        clock.advance(Duration::from_millis(6));
+        assert_eq!(clock.tick(), Duration::from_millis(16));
+        assert_eq!(clock.tick(), Duration::ZERO);
+    }
+
+    #[test]
+    fn manual_clock_clones_share_their_time() {
+        let driver = ManualClock::default();
+        let mut clock: Box<dyn Clock> = Box::new(driver.clone());
+
+        driver.advance(Duration::from_millis(40));
+        assert_eq!(clock.tick(), Duration::from_millis(40));
+
+        let mut copy = driver.clone();
+        driver.advance(Duration::from_millis(8));
+        assert_eq!(copy.tick(), Duration::from_millis(8));
+        assert_eq!(clock.tick(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,388 @@
+//! Problems found in the effect DSL or the canvas, in a form shared by all front
+//! ends: the web editor receives them as JSON through `dsl_error_callback`, the
+//! previewer shows them in the error popup and the CLI prints them.
+
+use std::fmt;
+use std::ops::Range;
+use serde::Serialize;
+use tachyonfx::dsl::{DslError, EffectDsl};
+use tachyonfx::Effect;
+use crate::lexer::{closing_bracket, tokenize, Token, TokenKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// What a diagnostic refers to; only DSL diagnostics have spans.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticSource {
+    Dsl,
+    Canvas,
+}
+
+/// A range of the source. Lines and columns are 1-based and count characters;
+/// the end column is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+/// Another location relevant to a diagnostic, e.g. where an unclosed bracket
+/// was opened.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RelatedSpan {
+    pub span: Span,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub source: DiagnosticSource,
+    pub span: Option<Span>,
+    pub message: String,
+    pub hints: Vec<String>,
+    pub related: Vec<RelatedSpan>,
+}
+
+/// Compiles the effect DSL, reporting every problem found when it fails.
+///
+/// The tachyonfx compiler stops at the first error; unbalanced brackets are
+/// checked separately, as they often cause a misleading error further on.
+pub fn compile_dsl(source: &str) -> Result<Effect, Vec<Diagnostic>> {
+    EffectDsl::new()
+        .compiler()
+        .compile(source)
+        .map_err(|e| {
+            let brackets = check_brackets(source);
+            if !brackets.is_empty() {
+                return brackets;
+            }
+
+            let span = Span {
+                start_line: e.start_line(),
+                start_column: e.start_column(),
+                end_line: e.end_line(),
+                end_column: e.end_column(),
+            };
+            let span = named_token(&e.source)
+                .and_then(|name| find_identifier(source, name, span))
+                .unwrap_or(span);
+            vec![Diagnostic::from_dsl_error(&e.source).with_span(span)]
+        })
+}
+
+/// the diagnostics serialized for the web front end: `{"diagnostics": [...]}`
+pub fn diagnostics_json(diagnostics: &[Diagnostic]) -> String {
+    serde_json::json!({ "diagnostics": diagnostics }).to_string()
+}
+
+impl Diagnostic {
+    pub fn error(source: DiagnosticSource, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            source,
+            span: None,
+            message: message.into(),
+            hints: Vec::new(),
+            related: Vec::new(),
+        }
+    }
+
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hints.push(hint.into());
+        self
+    }
+
+    pub fn with_related(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.related.push(RelatedSpan { span, message: message.into() });
+        self
+    }
+
+    /// The first sentence of the tachyonfx message becomes the message and the
+    /// advice following it a hint. Byte positions are left out; the span holds
+    /// the location.
+    fn from_dsl_error(e: &DslError) -> Self {
+        let text = without_byte_position(&e.to_string());
+        let (message, advice) = match text.split_once(". ") {
+            Some((message, advice)) => (format!("{message}."), Some(advice.trim())),
+            None                    => (text.clone(), None),
+        };
+
+        let diagnostic = Diagnostic::error(DiagnosticSource::Dsl, message);
+        match advice.filter(|advice| !advice.is_empty()) {
+            Some(advice) => diagnostic.with_hint(advice),
+            None         => diagnostic,
+        }
+    }
+
+    /// The lines around the span, each with the characters covered by the
+    /// span; empty without a span.
+    pub fn excerpt<'a>(&self, source: &'a str) -> Vec<ExcerptLine<'a>> {
+        let Some(span) = self.span else { return Vec::new() };
+
+        const CONTEXT_LINES: usize = 2;
+        let first = span.start_line.saturating_sub(CONTEXT_LINES + 1);
+        let last = span.end_line + CONTEXT_LINES;
+
+        source.lines()
+            .enumerate()
+            .take(last)
+            .skip(first)
+            .map(|(i, text)| {
+                let number = i + 1;
+                let marked = (span.start_line..=span.end_line).contains(&number).then(|| {
+                    let start = if number == span.start_line { span.start_column - 1 } else { 0 };
+                    let end = match number == span.end_line {
+                        true  => span.end_column - 1,
+                        false => text.chars().count(),
+                    };
+                    // empty spans still get a caret
+                    start..end.max(start + 1)
+                });
+
+                ExcerptLine { number, text, marked }
+            })
+            .collect()
+    }
+}
+
+/// A line of source shown along with a diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExcerptLine<'a> {
+    /// 1-based line number
+    pub number: usize,
+    pub text: &'a str,
+    /// the characters covered by the span, if the line is part of it
+    pub marked: Option<Range<usize>>,
+}
+
+impl Span {
+    /// the span of the byte range `start..end` in `source`
+    pub fn from_offsets(source: &str, start: usize, end: usize) -> Self {
+        let (start_line, start_column) = line_column(source, start);
+        let (end_line, end_column) = line_column(source, end);
+        Self { start_line, start_column, end_line, end_column }
+    }
+}
+
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+    (line, column)
+}
+
+/// removes " at position (start..end)", as written by some tachyonfx errors
+fn without_byte_position(message: &str) -> String {
+    const PREFIX: &str = " at position (";
+    let range = message.find(PREFIX).and_then(|start| {
+        let end = start + PREFIX.len() + message[start + PREFIX.len()..].find(')')?;
+        let is_byte_range = message[start + PREFIX.len()..end]
+            .split_once("..")
+            .is_some_and(|(a, b)| [a, b].iter().all(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit())));
+        is_byte_range.then_some(start..end + 1)
+    });
+
+    match range {
+        Some(range) => format!("{}{}", &message[..range.start], &message[range.end..]),
+        None        => message.to_string(),
+    }
+}
+
+/// the identifier an error is about, if any
+fn named_token(e: &DslError) -> Option<&str> {
+    match e {
+        DslError::UnknownEffect { name, .. }
+        | DslError::UnknownArgument { name, .. }
+        | DslError::UnknownFunction { name, .. }
+        | DslError::UnknownStruct { name, .. }
+        | DslError::UnknownCellFilter { name, .. } => Some(name),
+        DslError::UnknownField { field, .. }        => Some(field),
+        _                                           => None,
+    }
+}
+
+/// Narrows a span to the first occurrence of the identifier `name` within it.
+/// The compiler reports some errors, e.g. unknown top-level effects, with an
+/// empty span; these are searched for in the whole source.
+fn find_identifier(source: &str, name: &str, span: Span) -> Option<Span> {
+    let start = (span.start_line, span.start_column);
+    let end = (span.end_line, span.end_column);
+    let is_empty = start >= end;
+
+    tokenize(source).iter()
+        .filter(|token| token.kind == TokenKind::Ident && token.text == name)
+        .map(|token| Span::from_offsets(source, token.start, token.end()))
+        .find(|s| is_empty || (start <= (s.start_line, s.start_column) && (s.end_line, s.end_column) <= end))
+}
+
+/// every unmatched or mismatched bracket, ignoring brackets in strings and comments
+pub(crate) fn check_brackets(source: &str) -> Vec<Diagnostic> {
+    let span = |token: &Token| Span::from_offsets(source, token.start, token.end());
+    let error = |message: String| Diagnostic::error(DiagnosticSource::Dsl, message);
+
+    let mut diagnostics = Vec::new();
+    let mut open: Vec<(char, Token)> = Vec::new();
+    for token in tokenize(source) {
+        match token.kind {
+            TokenKind::Open(c)  => open.push((c, token)),
+            TokenKind::Close(c) => match open.pop() {
+                Some((o, _)) if closing_bracket(o) == c => {}
+                Some((o, opening)) => diagnostics.push(
+                    error(format!("Mismatched closing '{c}'; expected '{}'.", closing_bracket(o)))
+                        .with_span(span(&token))
+                        .with_related(span(&opening), format!("'{o}' opened here."))
+                ),
+                None => diagnostics.push(error(format!("Unmatched closing '{c}'."))
+                    .with_span(span(&token))
+                    .with_hint("Remove it, or add the opening bracket it belongs to.")),
+            },
+            _ => {}
+        }
+    }
+
+    diagnostics.extend(open.iter().map(|(o, opening)| {
+        error(format!("Unclosed '{o}'."))
+            .with_span(span(opening))
+            .with_hint(format!("Add a matching '{}'.", closing_bracket(*o)))
+    }));
+    diagnostics.sort_by_key(|d| d.span.map(|s| (s.start_line, s.start_column)));
+
+    diagnostics
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error   => f.write_str("error"),
+            Severity::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.start_line, self.start_column)
+    }
+}
+
+/// `line:column: severity: message`, followed by the hints and related spans
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(span) = self.span {
+            write!(f, "{span}: ")?;
+        }
+        write!(f, "{}: {}", self.severity, self.message)?;
+        for hint in &self.hints {
+            write!(f, "\n  hint: {hint}")?;
+        }
+        for related in &self.related {
+            write!(f, "\n  {}: {}", related.span, related.message)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostics(source: &str) -> Vec<Diagnostic> {
+        compile_dsl(source).expect_err("the source should fail to compile")
+    }
+
+    fn span(start: (usize, usize), end: (usize, usize)) -> Span {
+        Span { start_line: start.0, start_column: start.1, end_line: end.0, end_column: end.1 }
+    }
+
+    #[test]
+    fn advice_after_the_first_sentence_becomes_a_hint() {
+        let [diagnostic] = &diagnostics("fx::nope(500)")[..] else { panic!("expected one diagnostic") };
+
+        assert_eq!(diagnostic.message, "Unknown effect 'nope'.");
+        assert_eq!(diagnostic.hints, ["Check the effect name or register the effect with EffectDsl::register."]);
+        assert_eq!(diagnostic.span, Some(span((1, 5), (1, 9))));
+    }
+
+    #[test]
+    fn byte_positions_are_left_out_of_the_message() {
+        let [diagnostic] = &diagnostics("fx::dissolve(500) 12")[..] else { panic!("expected one diagnostic") };
+
+        assert_eq!(diagnostic.message, "Failed to parse expression.");
+        assert_eq!(diagnostic.hints, ["This could be due to unexpected tokens or invalid syntax."]);
+        assert_eq!(diagnostic.span, Some(span((1, 19), (1, 21))));
+    }
+
+    #[test]
+    fn single_sentence_messages_have_no_hints() {
+        let [diagnostic] = &diagnostics("fx::dissolve(Foo::bar(1))")[..] else { panic!("expected one diagnostic") };
+
+        assert_eq!(diagnostic.message, "Type mismatch: expected 'timer' but found 'Foo::bar'.");
+        assert!(diagnostic.hints.is_empty());
+    }
+
+    #[test]
+    fn unknown_variables_are_narrowed_to_the_identifier() {
+        let [diagnostic] = &diagnostics("let t = 500;\nfx::dissolve(x)")[..] else { panic!("expected one diagnostic") };
+
+        assert_eq!(diagnostic.message, "Variable 'x' not found.");
+        assert_eq!(diagnostic.span, Some(span((2, 14), (2, 15))));
+    }
+
+    #[test]
+    fn every_unbalanced_bracket_is_reported() {
+        let diagnostics = check_brackets("fx::sequence(&[\n    fx::dissolve(500))\n");
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+
+        assert_eq!(messages, ["Unclosed '('.", "Mismatched closing ')'; expected ']'."]);
+        assert_eq!(diagnostics[0].span, Some(span((1, 13), (1, 14))));
+        assert_eq!(diagnostics[1].span, Some(span((2, 22), (2, 23))));
+        assert_eq!(diagnostics[1].related[0].span, span((1, 15), (1, 16)));
+    }
+
+    #[test]
+    fn unmatched_closing_brackets_are_reported() {
+        let [diagnostic] = &check_brackets("fx::dissolve(500))")[..] else { panic!("expected one diagnostic") };
+
+        assert_eq!(diagnostic.message, "Unmatched closing ')'.");
+        assert_eq!(diagnostic.span, Some(span((1, 18), (1, 19))));
+    }
+
+    #[test]
+    fn brackets_in_strings_and_comments_are_ignored() {
+        let source = "// (\nfx::dissolve(500) /* [ */.with_filter(CellFilter::Text) // \")\"";
+        assert!(check_brackets(source).is_empty());
+    }
+
+    #[test]
+    fn bracket_errors_take_precedence_over_the_compiler_error() {
+        let diagnostics = diagnostics("fx::dissolve(500");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "Unclosed '('.");
+    }
+
+    #[test]
+    fn excerpt_marks_the_span_with_context_lines() {
+        let source = "a\nb\nc\nfx::nope(1)\nd\ne\nf";
+        let diagnostic = Diagnostic::error(DiagnosticSource::Dsl, "").with_span(span((4, 5), (4, 9)));
+
+        let excerpt = diagnostic.excerpt(source);
+        let numbers: Vec<_> = excerpt.iter().map(|line| line.number).collect();
+        assert_eq!(numbers, [2, 3, 4, 5, 6]);
+        assert_eq!(excerpt[2].marked, Some(4..8));
+        assert!(excerpt.iter().filter(|line| line.number != 4).all(|line| line.marked.is_none()));
+    }
+}
@@ -0,0 +1,391 @@
+//! A structural view of an effect and the progress of each of its effects, used
+//! by the inspector.
+//!
+//! tachyonfx keeps the children of composite effects private, so the tree follows
+//! the DSL source instead: every effect call is compiled on its own and described
+//! through the `Shader` API. Start times follow the same rules as
+//! `Shader::as_effect_span`.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use tachyonfx::dsl::EffectDsl;
+use tachyonfx::{Duration, Effect, Shader};
+use crate::catalog::{self, SymbolKind};
+use crate::lexer::{tokenize, Token, TokenKind};
+
+/// A single effect within the effect tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectNode {
+    /// name of the effect, as reported by `Shader::name`
+    pub name: String,
+    /// the cell filter, unless the effect applies to all cells
+    pub cell_filter: Option<String>,
+    /// when the effect starts, relative to the root effect
+    pub start: Duration,
+    /// duration of the effect; zero for effects without a timer
+    pub duration: Duration,
+    /// interpolation passed to the effect, as written in the source
+    pub interpolation: Option<String>,
+    /// time spent in the effect
+    pub elapsed: Duration,
+    /// whether the effect has run to completion
+    pub done: bool,
+    pub children: Vec<EffectNode>,
+}
+
+impl EffectNode {
+    /// Builds the tree for the effect compiled from `source`; `None` if the
+    /// source doesn't end with an effect call.
+    pub fn from_dsl(source: &str) -> Option<EffectNode> {
+        let tree = SourceTree::new(source);
+        let root = tree.root()?;
+        Some(tree.node(root, Duration::ZERO))
+    }
+
+    pub fn end(&self) -> Duration {
+        self.start + self.duration
+    }
+
+    /// time left until the effect completes
+    pub fn remaining(&self) -> Duration {
+        self.duration.checked_sub(self.elapsed).unwrap_or_default()
+    }
+
+    /// Updates the progress of every effect from the running `effect`, which
+    /// has been playing for `playhead`. Only the root effect can be asked for
+    /// its state; nested effects are timed against the playhead.
+    pub fn refresh(&mut self, effect: &Effect, playhead: Duration) {
+        self.update(playhead, Some(effect.done()));
+
+        // the timer of a composite effect is derived from its children, not live
+        if let Some(timer) = effect.timer().filter(|_| self.children.is_empty()) {
+            self.elapsed = timer.duration().checked_sub(timer.remaining()).unwrap_or_default();
+        }
+    }
+
+    /// all nodes in depth-first order, paired with their depth
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &EffectNode)> {
+        let mut stack = vec![(0, self)];
+        std::iter::from_fn(move || {
+            let (depth, node) = stack.pop()?;
+            stack.extend(node.children.iter().rev().map(|child| (depth + 1, child)));
+            Some((depth, node))
+        })
+    }
+
+    /// `local` is the time on the parent's clock; `done` overrides the
+    /// timing, e.g. once the parent is done.
+    fn update(&mut self, local: Duration, done: Option<bool>) {
+        self.elapsed = local.checked_sub(self.start).unwrap_or_default().min(self.duration);
+        self.done = done.unwrap_or(!self.duration.is_zero() && local >= self.end());
+
+        // repeated effects restart on every iteration
+        let period = self.children.first().map(|c| c.duration).unwrap_or_default();
+        let child_local = match self.name.as_str() {
+            _ if self.done || period.is_zero() => local,
+            "repeat" => {
+                let t = self.elapsed.as_millis() % period.as_millis();
+                self.start + Duration::from_millis(t)
+            }
+            "ping_pong" => {
+                let (t, period) = (self.elapsed.as_millis(), period.as_millis());
+                let t = if t < period { t } else { (2 * period).saturating_sub(t) };
+                self.start + Duration::from_millis(t)
+            }
+            _ => local,
+        };
+
+        let done = self.done.then_some(true);
+        self.children.iter_mut().for_each(|child| child.update(child_local, done));
+    }
+
+    fn shift(&mut self, offset: Duration) {
+        self.start += offset;
+        self.children.iter_mut().for_each(|child| child.shift(offset));
+    }
+}
+
+/// An effect call in the source: `fx::name(args)`, along with any methods
+/// chained onto it. Ranges are token indices.
+#[derive(Debug, Clone)]
+struct Call {
+    /// the whole expression, including the method chain
+    tokens: Range<usize>,
+    /// the arguments, between the parentheses
+    args: Range<usize>,
+}
+
+struct SourceTree<'a> {
+    source: &'a str,
+    /// tokens without comments
+    tokens: Vec<Token<'a>>,
+    /// index of the matching closing bracket, for each opening bracket
+    closing: HashMap<usize, usize>,
+    /// `let` statements before the effect, up to the last `;`
+    prelude: &'a str,
+    /// index of the first token of the value bound to each variable
+    bindings: HashMap<&'a str, usize>,
+}
+
+impl<'a> SourceTree<'a> {
+    fn new(source: &'a str) -> Self {
+        let tokens: Vec<_> = tokenize(source).into_iter()
+            .filter(|t| !matches!(t.kind, TokenKind::LineComment | TokenKind::BlockComment))
+            .collect();
+
+        let mut closing = HashMap::new();
+        let mut open = Vec::new();
+        let mut prelude = "";
+        let mut bindings = HashMap::new();
+        for (i, token) in tokens.iter().enumerate() {
+            match token.kind {
+                TokenKind::Open(_)  => open.push(i),
+                TokenKind::Close(_) => if let Some(o) = open.pop() { closing.insert(o, i); },
+                TokenKind::Punct(';') if open.is_empty() => prelude = &source[..token.end()],
+                TokenKind::Ident if open.is_empty() && token.text == "let" => {
+                    if let [name, eq, ..] = &tokens[i + 1..] {
+                        if name.kind == TokenKind::Ident && eq.kind == TokenKind::Punct('=') {
+                            bindings.insert(name.text, i + 3);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self { source, tokens, closing, prelude, bindings }
+    }
+
+    /// the effect the source evaluates to, following the `let` statements
+    fn root(&self) -> Option<Call> {
+        let first = self.tokens.iter()
+            .position(|t| t.start >= self.prelude.len())?;
+        self.effect(first, usize::MAX)
+    }
+
+    /// The path starting at token `i`, e.g. `fx::dissolve`, and the index of
+    /// its last token.
+    fn path(&self, i: usize) -> Option<(&'a str, usize)> {
+        let is_separator = |j: usize| self.tokens.get(j..j + 2)
+            .is_some_and(|t| t.iter().all(|t| t.kind == TokenKind::Punct(':')));
+
+        if self.tokens.get(i)?.kind != TokenKind::Ident {
+            return None;
+        }
+
+        let mut last = i;
+        while is_separator(last + 1) && self.tokens.get(last + 3).is_some_and(|t| t.kind == TokenKind::Ident) {
+            last += 3;
+        }
+
+        Some((&self.source[self.tokens[i].start..self.tokens[last].end()], last))
+    }
+
+    /// The effect starting at token `i`: either an effect call or a variable
+    /// bound to one. Variables resolve only to bindings before `i`.
+    fn effect(&self, i: usize, limit: usize) -> Option<Call> {
+        let (path, last) = self.path(i)?;
+        let is_call = self.tokens.get(last + 1).is_some_and(|t| t.kind == TokenKind::Open('('));
+        if !is_call {
+            let value = *self.bindings.get(path).filter(|&&value| value < i.min(limit))?;
+            return self.effect(value, value);
+        }
+
+        catalog::find(path).filter(|s| s.kind == SymbolKind::Effect)?;
+        let open = last + 1;
+        let close = *self.closing.get(&open)?;
+
+        // method chain, e.g. `.with_filter(..)`
+        let mut end = close + 1;
+        while let [dot, method, paren, ..] = &self.tokens[end.min(self.tokens.len())..] {
+            if dot.kind != TokenKind::Punct('.') || method.kind != TokenKind::Ident || paren.kind != TokenKind::Open('(') {
+                break;
+            }
+            end = self.closing.get(&(end + 2)).map_or(self.tokens.len(), |&c| c + 1);
+        }
+
+        Some(Call { tokens: i..end, args: open + 1..close })
+    }
+
+    fn text(&self, tokens: &Range<usize>) -> &'a str {
+        let end = self.tokens.get(tokens.end - 1).map_or(self.source.len(), Token::end);
+        &self.source[self.tokens[tokens.start].start..end]
+    }
+
+    /// Compiles `call` on its own, preceded by the `let` statements, and
+    /// builds its node and those of the effects in its arguments.
+    fn node(&self, call: Call, start: Duration) -> EffectNode {
+        let effect = EffectDsl::new()
+            .compiler()
+            .compile(&format!("{}\n{}", self.prelude, self.text(&call.tokens)))
+            .ok();
+
+        let mut children = Vec::new();
+        let mut interpolation = None;
+        let mut i = call.args.start;
+        while i < call.args.end {
+            if let Some(child) = self.effect(i, call.tokens.start) {
+                i = match child.tokens.start >= call.tokens.start {
+                    true  => child.tokens.end,
+                    false => i + 1, // a variable
+                };
+                children.push(self.node(child, start));
+                continue;
+            }
+
+            if let Some((path, last)) = self.path(i) {
+                let is_interpolation = catalog::find(path)
+                    .is_some_and(|s| s.namespace == "Interpolation" && s.kind == SymbolKind::Variant);
+                if is_interpolation && interpolation.is_none() {
+                    interpolation = path.rsplit("::").next().map(str::to_string);
+                }
+                i = last;
+            }
+
+            i += 1;
+        }
+
+        let name = effect.as_ref()
+            .map(|e| e.name().to_string())
+            .unwrap_or_else(|| self.text(&(call.tokens.start..call.args.start - 1)).to_string());
+        let duration = effect.as_ref()
+            .and_then(Shader::timer)
+            .map(|timer| timer.duration())
+            .unwrap_or_default();
+
+        match name.as_str() {
+            "sequence" => {
+                let mut offset = Duration::ZERO;
+                children.iter_mut().for_each(|child| {
+                    child.shift(offset);
+                    offset += child.duration;
+                });
+            }
+            "prolong_start" => children.iter_mut().for_each(|child| {
+                child.shift(duration.checked_sub(child.duration).unwrap_or_default());
+            }),
+            _ => {}
+        }
+
+        EffectNode {
+            name,
+            cell_filter: effect.as_ref()
+                .and_then(Shader::cell_filter)
+                .map(|filter| filter.to_string())
+                .filter(|filter| filter != "all"),
+            start,
+            duration,
+            interpolation,
+            elapsed: Duration::ZERO,
+            done: false,
+            children,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use super::*;
+
+    const SOURCE: &str = "\
+let fade = fx::fade_from_fg(Color::Blue, 40);
+fx::sequence(&[
+    fx::parallel(&[
+        fx::fade_to_fg(Color::Red, (100, QuadOut)),
+        fx::coalesce(200).with_filter(CellFilter::Text),
+    ]),
+    fx::repeat(fx::fade_to_fg(Color::Green, 50), RepeatMode::Times(2)),
+    fx::ping_pong(fade),
+])";
+
+    fn ms(ms: u32) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    /// the tree of `SOURCE` after playing the effect for `playhead`
+    fn play(playhead: Duration) -> (EffectNode, Effect) {
+        let mut effect = EffectDsl::new().compiler().compile(SOURCE).unwrap();
+        let area = Rect::new(0, 0, 4, 1);
+        let mut buf = Buffer::empty(area);
+        // frame by frame, as in the app
+        for _ in 0..playhead.as_millis() / 5 {
+            effect.process(ms(5), &mut buf, area);
+        }
+
+        let mut tree = EffectNode::from_dsl(SOURCE).unwrap();
+        tree.refresh(&effect, playhead);
+        (tree, effect)
+    }
+
+    /// depth, name, elapsed and done of every node
+    fn progress(tree: &EffectNode) -> Vec<(usize, &str, u32, bool)> {
+        tree.iter()
+            .map(|(depth, node)| (depth, node.name.as_str(), node.elapsed.as_millis(), node.done))
+            .collect()
+    }
+
+    #[test]
+    fn follows_the_nesting_and_timing_of_the_source() {
+        let tree = EffectNode::from_dsl(SOURCE).unwrap();
+        let nodes: Vec<_> = tree.iter()
+            .map(|(depth, node)| (depth, node.name.as_str(), node.start.as_millis(), node.duration.as_millis()))
+            .collect();
+
+        assert_eq!(nodes, [
+            (0, "sequence", 0, 380),
+            (1, "parallel", 0, 200),
+            (2, "fade_to", 0, 100),
+            (2, "coalesce", 0, 200),
+            (1, "repeat", 200, 100),
+            (2, "fade_to", 200, 50),
+            (1, "ping_pong", 300, 80),
+            (2, "fade_from", 300, 40),
+        ]);
+        assert_eq!(tree.end(), ms(380));
+
+        let labels = |node: &EffectNode| (node.cell_filter.clone(), node.interpolation.clone());
+        let parallel = &tree.children[0];
+        assert_eq!(labels(&parallel.children[0]), (None, Some("QuadOut".into())));
+        assert_eq!(labels(&parallel.children[1]), (Some("text".into()), None));
+    }
+
+    #[test]
+    fn nodes_are_timed_against_the_playhead() {
+        let (tree, _) = play(ms(225));
+        assert_eq!(progress(&tree), [
+            (0, "sequence", 225, false),
+            (1, "parallel", 200, true),
+            (2, "fade_to", 100, true),
+            (2, "coalesce", 200, true),
+            (1, "repeat", 25, false),
+            (2, "fade_to", 25, false),
+            (1, "ping_pong", 0, false),
+            (2, "fade_from", 0, false),
+        ]);
+        assert_eq!(tree.remaining(), ms(155));
+    }
+
+    #[test]
+    fn repeated_effects_restart_and_ping_pong_plays_backwards() {
+        let (tree, _) = play(ms(350));
+        assert_eq!(progress(&tree)[4..], [
+            (1, "repeat", 100, true),
+            (2, "fade_to", 50, true),
+            (1, "ping_pong", 50, false),
+            (2, "fade_from", 30, false),
+        ]);
+
+        let (tree, _) = play(ms(275));
+        assert_eq!(progress(&tree)[4..6], [(1, "repeat", 75, false), (2, "fade_to", 25, false)]);
+    }
+
+    #[test]
+    fn every_node_is_done_once_the_effect_is() {
+        let (tree, effect) = play(ms(400));
+        assert!(effect.done());
+        assert!(tree.iter().all(|(_, node)| node.done && node.remaining() == Duration::ZERO));
+        assert_eq!(tree.elapsed, ms(380));
+    }
+}
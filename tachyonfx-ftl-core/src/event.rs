@@ -1,14 +1,59 @@
 use bitflags::bitflags;
+use tachyonfx::Duration;
+use crate::app::TimelinePanel;
 
 pub enum AppEvent {
     Tick,
     KeyPress(KeyEvent),
     Resize(u16, u16),
     UpdateCanvas(String),
+    /// replaces the canvas with a widget layout; see [canvas::layout](crate::canvas::layout)
+    UpdateCanvasLayout(String),
     CompileDsl(String),
+    /// pauses or resumes playback of the editor effect
+    TogglePause,
+    /// restarts the editor effect from t=0
+    Restart,
+    /// advances the editor effect by a single frame; pauses playback
+    StepForward,
+    /// rewinds the editor effect by a single frame; pauses playback
+    StepBackward,
+    /// sets the playback speed of the editor effect
+    SetTimeScale(f32),
+    /// switches to the next faster playback speed
+    IncreaseTimeScale,
+    /// switches to the next slower playback speed
+    DecreaseTimeScale,
+    /// toggles between slow-motion and normal playback speed
+    ToggleSlowMotion,
+    /// moves the editor effect to the given timestamp
+    Seek(Duration),
+    /// moves the editor effect forward by the given duration
+    SeekForward(Duration),
+    /// moves the editor effect backward by the given duration
+    SeekBackward(Duration),
+    /// enables looping with the given pause between runs; `None` disables looping
+    SetLoop(Option<Duration>),
+    /// enables or disables looping of the editor effect
+    ToggleLoop,
+    /// cycles the effect timeline between hidden, bottom and side panel
+    ToggleTimeline,
+    /// shows the effect timeline at the given position, or hides it
+    SetTimelinePanel(TimelinePanel),
+    /// shows or hides the effect inspector
+    ToggleInspector,
+    /// shows the effect inspector when `true`, hides it otherwise
+    ShowInspector(bool),
+    /// scrolls the error popup by the given number of lines; negative values scroll up
+    ScrollErrorPopup(i16),
+    /// hides the error popup
+    DismissErrorPopup,
     Quit,
 }
 
+/// lines scrolled by page up and page down in the error popup
+pub const ERROR_POPUP_PAGE: i16 = 10;
+
 pub struct KeyEvent {
     pub key_code: KeyCode,
     pub modifier_keys: ModifierKeys
@@ -0,0 +1,61 @@
+//! Writes frames as an animated PNG.
+
+use std::io::{self, Write};
+use png::{BitDepth, ColorType, Encoder};
+use crate::export::frame_durations;
+use crate::export::raster::Rasterizer;
+use crate::headless::Frame;
+
+/// Writes `frames` as an APNG looping forever. Unlike GIF, colors are kept
+/// as they are and frame delays are exact to the millisecond.
+pub fn write_apng(frames: &[Frame], rasterizer: &Rasterizer, out: impl Write) -> io::Result<()> {
+    let frames = frame_durations(frames);
+    let Some((first, _)) = frames.first() else { return Ok(()) };
+
+    let (width, height) = rasterizer.image_size(first.buffer.area);
+    let mut encoder = Encoder::new(out, width, height);
+    encoder.set_color(ColorType::Rgb);
+    encoder.set_depth(BitDepth::Eight);
+    encoder.set_animated(frames.len() as u32, 0)?;
+
+    let mut writer = encoder.write_header()?;
+    for (frame, shown) in &frames {
+        let delay = shown.as_millis().min(u16::MAX as u32) as u16;
+        writer.set_frame_delay(delay, 1000)?;
+        writer.write_image_data(&rasterizer.rasterize(&frame.buffer))?;
+    }
+
+    writer.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::buffer::Buffer;
+    use tachyonfx::Duration;
+    use super::*;
+
+    #[test]
+    fn writes_an_apng_with_a_frame_per_distinct_buffer() {
+        let frames: Vec<_> = [(0, "ab"), (16, "ab"), (32, "cd"), (48, "ef")].into_iter()
+            .map(|(ms, text)| Frame { timestamp: Duration::from_millis(ms), buffer: Buffer::with_lines([text]) })
+            .collect();
+
+        let mut apng = Vec::new();
+        write_apng(&frames, &Rasterizer::new(6, 10), &mut apng).unwrap();
+
+        let mut reader = png::Decoder::new(apng.as_slice()).read_info().unwrap();
+        let info = reader.info();
+        assert_eq!((info.width, info.height), (12, 10));
+        assert_eq!(info.animation_control.map(|a| (a.num_frames, a.num_plays)), Some((3, 0)));
+
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let mut delays = Vec::new();
+        while let Ok(output) = reader.next_frame(&mut pixels) {
+            assert_eq!((output.width, output.height), (12, 10));
+            let control = reader.info().frame_control.unwrap();
+            delays.push((control.delay_num, control.delay_den));
+        }
+        assert_eq!(delays, [(32, 1000), (16, 1000), (16, 1000)]);
+    }
+}
@@ -0,0 +1,135 @@
+//! Writes frames as an [asciicast v2](https://docs.asciinema.org/manual/asciicast/v2/)
+//! recording, for playback with asciinema.
+
+use std::io::{self, Write};
+use ratatui::buffer::Buffer;
+use serde_json::json;
+use crate::canvas::buffer_to_ansi;
+use crate::headless::Frame;
+
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const CLEAR_SCREEN: &str = "\x1b[2J";
+const CURSOR_HOME: &str = "\x1b[H";
+
+/// Writes the header followed by one output event per frame. Frames identical
+/// to the previous one are skipped, as the player holds the last frame anyway.
+pub fn write_asciicast(frames: &[Frame], mut out: impl Write) -> io::Result<()> {
+    let size = frames.first()
+        .map(|f| f.buffer.area.as_size())
+        .unwrap_or_default();
+
+    let header = json!({
+        "version": 2,
+        "width": size.width,
+        "height": size.height,
+        "env": { "TERM": "xterm-256color" },
+    });
+    writeln!(out, "{header}")?;
+
+    let mut previous: Option<&Buffer> = None;
+    for frame in frames {
+        if previous == Some(&frame.buffer) {
+            continue;
+        }
+
+        let prefix = match previous {
+            None    => format!("{HIDE_CURSOR}{CLEAR_SCREEN}{CURSOR_HOME}"),
+            Some(_) => CURSOR_HOME.to_string(),
+        };
+
+        let event = json!([
+            frame.timestamp.as_millis() as f64 / 1000.0,
+            "o",
+            prefix + &to_terminal_output(&frame.buffer),
+        ]);
+        writeln!(out, "{event}")?;
+
+        previous = Some(&frame.buffer);
+    }
+
+    Ok(())
+}
+
+/// ANSI-escaped content of `buf`, with line breaks suitable for a raw terminal.
+/// The last line isn't terminated, as that would scroll a terminal of the same
+/// height as the buffer.
+fn to_terminal_output(buf: &Buffer) -> String {
+    buffer_to_ansi(buf).replace('\n', "\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::layout::Rect;
+    use serde_json::Value;
+    use tachyonfx::Duration;
+    use super::*;
+
+    fn frame(timestamp: u32, lines: [&str; 2]) -> Frame {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 3, 2));
+        lines.iter().enumerate().for_each(|(y, line)| {
+            buffer.set_string(0, y as u16, line, ratatui::style::Style::default());
+        });
+        Frame { timestamp: Duration::from_millis(timestamp), buffer }
+    }
+
+    fn events(frames: &[Frame]) -> Vec<Value> {
+        let mut out = Vec::new();
+        write_asciicast(frames, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn writes_the_header_and_a_timed_event_per_frame() {
+        let events = events(&[frame(0, ["abc", "def"]), frame(1500, ["xyz", "def"])]);
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0]["version"], 2);
+        assert_eq!((events[0]["width"].as_u64(), events[0]["height"].as_u64()), (Some(3), Some(2)));
+        assert_eq!(events[1][0], 0.0);
+        assert_eq!(events[2][0], 1.5);
+        assert!(events[1][1] == "o" && events[2][1] == "o");
+    }
+
+    #[test]
+    fn clears_the_screen_once_and_uses_crlf_line_breaks() {
+        let events = events(&[frame(0, ["abc", "def"]), frame(16, ["xyz", "def"])]);
+        let output = |i: usize| events[i][2].as_str().unwrap().to_string();
+
+        assert!(output(1).starts_with(&format!("{HIDE_CURSOR}{CLEAR_SCREEN}{CURSOR_HOME}")));
+        assert!(output(2).starts_with(CURSOR_HOME) && !output(2).contains(CLEAR_SCREEN));
+        assert!(output(1).contains("\r\n"));
+        assert!(!output(1).ends_with('\n'));
+    }
+
+    #[test]
+    fn skips_frames_identical_to_the_previous_one() {
+        let events = events(&[frame(0, ["abc", "def"]), frame(16, ["abc", "def"]), frame(32, ["xyz", "def"])]);
+
+        let timestamps: Vec<_> = events[1..].iter().map(|e| e[0].as_f64().unwrap()).collect();
+        assert_eq!(timestamps, [0.0, 0.032]);
+    }
+
+    #[test]
+    fn wide_characters_keep_the_row_width() {
+        let events = events(&[frame(0, ["a世", "def"])]);
+        let output = events[1][2].as_str().unwrap().replace(CURSOR_HOME, "");
+
+        // strips the escape sequences, leaving the text of each row
+        let mut text = String::new();
+        let mut chars = output.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\x1b' => { chars.by_ref().find(|c| c.is_ascii_alphabetic()); }
+                c      => text.push(c),
+            }
+        }
+
+        let widths: Vec<_> = text.split("\r\n")
+            .map(|row| ratatui::text::Span::raw(row).width())
+            .collect();
+        assert_eq!(widths, [3, 3]);
+    }
+}
@@ -0,0 +1,140 @@
+//! Writes frames as an animated GIF.
+
+use std::io::{self, Write};
+use ::gif::{Encoder, Repeat};
+use tachyonfx::Duration;
+use crate::export::frame_durations;
+use crate::export::raster::Rasterizer;
+use crate::headless::Frame;
+
+/// Viewers clamp shorter frame delays, slowing the animation down, so frames
+/// are dropped until each is shown for at least this long; in centiseconds.
+const MIN_DELAY: u32 = 2;
+
+/// Writes `frames` as a GIF looping forever. Each frame is quantized to its own
+/// 256 color palette.
+pub fn write_gif(frames: &[Frame], rasterizer: &Rasterizer, out: impl Write) -> io::Result<()> {
+    let frames = frame_durations(frames);
+    let Some((first, _)) = frames.first() else { return Ok(()) };
+
+    let (width, height) = rasterizer.image_size(first.buffer.area);
+    let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "image too large for GIF");
+    let width = u16::try_from(width).map_err(|_| too_large())?;
+    let height = u16::try_from(height).map_err(|_| too_large())?;
+
+    let mut encoder = Encoder::new(out, width, height, &[]).map_err(io::Error::other)?;
+    encoder.set_repeat(Repeat::Infinite).map_err(io::Error::other)?;
+
+    for (frame, delay) in centisecond_delays(&frames) {
+        let pixels = rasterizer.rasterize(&frame.buffer);
+        let mut gif_frame = ::gif::Frame::from_rgb_speed(width, height, &pixels, 10);
+        gif_frame.delay = delay.min(u16::MAX as u32) as u16;
+        encoder.write_frame(&gif_frame).map_err(io::Error::other)?;
+    }
+
+    Ok(())
+}
+
+/// the frames to write with their delays, rounded to centiseconds without
+/// accumulating rounding errors
+fn centisecond_delays<'a>(frames: &[(&'a Frame, Duration)]) -> Vec<(&'a Frame, u32)> {
+    let start = frames.first().map(|(f, _)| f.timestamp.as_millis()).unwrap_or(0);
+    let to_cs = |ms: u32| (ms - start + 5) / 10;
+    let end = frames.last()
+        .map(|(f, shown)| to_cs(f.timestamp.as_millis() + shown.as_millis()))
+        .unwrap_or(0);
+
+    let mut kept: Vec<(&Frame, u32)> = Vec::new();
+    for (frame, _) in frames {
+        let t = to_cs(frame.timestamp.as_millis());
+        if kept.last().is_none_or(|(_, prev)| t >= prev + MIN_DELAY) {
+            kept.push((frame, t));
+        }
+    }
+
+    let next_starts: Vec<u32> = kept.iter()
+        .skip(1)
+        .map(|(_, t)| *t)
+        .chain(std::iter::once(end))
+        .collect();
+
+    kept.iter()
+        .zip(next_starts)
+        .map(|((frame, t), next)| (*frame, next.saturating_sub(*t).max(MIN_DELAY)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use crate::export::frame_durations;
+    use super::*;
+
+    /// frames `symbols` wide, one per timestamp; equal symbols give equal frames
+    fn frames(timestamps: &[u32], symbols: &str) -> Vec<Frame> {
+        timestamps.iter()
+            .zip(symbols.chars())
+            .map(|(&ms, c)| Frame {
+                timestamp: Duration::from_millis(ms),
+                buffer: Buffer::with_lines([c.to_string()]),
+            })
+            .collect()
+    }
+
+    fn delays(frames: &[Frame]) -> Vec<u32> {
+        centisecond_delays(&frame_durations(frames)).into_iter()
+            .map(|(_, delay)| delay)
+            .collect()
+    }
+
+    #[test]
+    fn rounding_errors_carry_over_to_the_next_frame() {
+        // 2, 3 and 3 centiseconds; the last frame is held for a frame step
+        let frames = frames(&[0, 16, 32, 48, 64], "abcde");
+        let kept = centisecond_delays(&frame_durations(&frames));
+        let symbols: Vec<_> = kept.iter().map(|(f, _)| f.buffer[(0, 0)].symbol()).collect();
+        assert_eq!(symbols, ["a", "b", "d"]);
+        assert_eq!(delays(&frames), [2, 3, 3]);
+        assert_eq!(delays(&frames).iter().sum::<u32>(), 8);
+    }
+
+    #[test]
+    fn delays_round_to_the_nearest_centisecond() {
+        assert_eq!(delays(&frames(&[0, 25, 50], "abc")), [3, 2, 3]);
+        assert_eq!(delays(&frames(&[0, 40, 80, 120], "abcd")), [4, 4, 4, 4]);
+    }
+
+    #[test]
+    fn unchanged_frames_extend_the_previous_delay() {
+        assert_eq!(delays(&frames(&[0, 16, 32], "aab")), [3, 2]);
+        assert_eq!(delays(&frames(&[0, 16, 32], "aaa")), [5]);
+    }
+
+    #[test]
+    fn writes_a_gif_with_a_frame_per_delay() {
+        let frames = frames(&[0, 40, 80, 120], "abcd");
+        let rasterizer = Rasterizer::new(6, 10);
+        let mut gif = Vec::new();
+        write_gif(&frames, &rasterizer, &mut gif).unwrap();
+
+        let mut options = ::gif::DecodeOptions::new();
+        options.set_color_output(::gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(gif.as_slice()).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (6, 10));
+
+        let mut delays = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!((frame.width, frame.height), (6, 10));
+            delays.push(frame.delay);
+        }
+        assert_eq!(delays, [4, 4, 4, 4]);
+    }
+
+    #[test]
+    fn rejects_images_too_large_for_a_gif() {
+        let frames = vec![Frame { timestamp: Duration::ZERO, buffer: Buffer::empty(Rect::new(0, 0, 8000, 1)) }];
+        let error = write_gif(&frames, &Rasterizer::default(), io::sink()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,135 @@
+//! Writes frames as [JSON Lines](https://jsonlines.org/), one object per frame,
+//! for tooling that compares effect output cell by cell.
+//!
+//! ```json
+//! {"cells":[[{"bg":"reset","fg":"#fbf1c7","modifiers":["bold"],"symbol":"a"},..]],"height":1,"timestamp":16,"width":2}
+//! ```
+
+use std::io::{self, Write};
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::style::Color;
+use serde_json::{json, Value};
+use crate::headless::Frame;
+
+/// Writes every frame, including those identical to their predecessor, so that
+/// line `n` always holds frame `n`. Timestamps are in milliseconds.
+pub fn write_json_lines(frames: &[Frame], mut out: impl Write) -> io::Result<()> {
+    for frame in frames {
+        writeln!(out, "{}", frame_json(frame))?;
+    }
+
+    Ok(())
+}
+
+/// a single line of the output
+pub fn frame_json(frame: &Frame) -> Value {
+    json!({
+        "timestamp": frame.timestamp.as_millis(),
+        "width": frame.buffer.area.width,
+        "height": frame.buffer.area.height,
+        "cells": cell_rows(&frame.buffer),
+    })
+}
+
+/// cells of `buffer`, one array per row
+fn cell_rows(buffer: &Buffer) -> Value {
+    let width = buffer.area.width.max(1) as usize;
+    buffer.content
+        .chunks(width)
+        .map(|row| row.iter().map(cell_json).collect::<Vec<_>>())
+        .collect()
+}
+
+/// symbol, colors and modifiers of `cell`
+pub fn cell_json(cell: &Cell) -> Value {
+    let modifiers: Vec<String> = cell.modifier
+        .iter_names()
+        .map(|(name, _)| name.to_lowercase())
+        .collect();
+
+    json!({
+        "symbol": cell.symbol(),
+        "fg": color_json(cell.fg),
+        "bg": color_json(cell.bg),
+        "modifiers": modifiers,
+    })
+}
+
+/// `"reset"`, named colors in snake case, `"#rrggbb"` or the index of an
+/// indexed color
+pub fn color_json(color: Color) -> Value {
+    let name = match color {
+        Color::Reset        => "reset",
+        Color::Black        => "black",
+        Color::Red          => "red",
+        Color::Green        => "green",
+        Color::Yellow       => "yellow",
+        Color::Blue         => "blue",
+        Color::Magenta      => "magenta",
+        Color::Cyan         => "cyan",
+        Color::Gray         => "gray",
+        Color::DarkGray     => "dark_gray",
+        Color::LightRed     => "light_red",
+        Color::LightGreen   => "light_green",
+        Color::LightYellow  => "light_yellow",
+        Color::LightBlue    => "light_blue",
+        Color::LightMagenta => "light_magenta",
+        Color::LightCyan    => "light_cyan",
+        Color::White        => "white",
+        Color::Rgb(r, g, b) => return json!(format!("#{r:02x}{g:02x}{b:02x}")),
+        Color::Indexed(i)   => return json!(i),
+    };
+
+    json!(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::layout::Rect;
+    use ratatui::style::{Modifier, Style};
+    use tachyonfx::Duration;
+    use super::*;
+
+    #[test]
+    fn writes_a_line_per_frame_with_every_cell() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 3, 2));
+        buffer.set_string(0, 0, "ab", Style::new()
+            .fg(Color::Rgb(0xfb, 0xf1, 0xc7))
+            .bg(Color::Indexed(208))
+            .add_modifier(Modifier::BOLD | Modifier::CROSSED_OUT));
+        buffer.set_string(0, 1, "c", Style::new().fg(Color::LightMagenta));
+
+        let frames = [
+            Frame { timestamp: Duration::ZERO, buffer: buffer.clone() },
+            Frame { timestamp: Duration::from_millis(16), buffer },
+        ];
+        let mut out = Vec::new();
+        write_json_lines(&frames, &mut out).unwrap();
+
+        let lines: Vec<Value> = String::from_utf8(out).unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+
+        let frame = &lines[1];
+        assert_eq!((&frame["timestamp"], &frame["width"], &frame["height"]), (&json!(16), &json!(3), &json!(2)));
+        assert_eq!(frame["cells"].as_array().map(Vec::len), Some(2));
+        assert_eq!(frame["cells"][0][0], json!({
+            "symbol": "a",
+            "fg": "#fbf1c7",
+            "bg": 208,
+            "modifiers": ["bold", "crossed_out"],
+        }));
+        assert_eq!(frame["cells"][1][0]["fg"], "light_magenta");
+        assert_eq!(frame["cells"][1][2], json!({ "symbol": " ", "fg": "reset", "bg": "reset", "modifiers": [] }));
+    }
+
+    #[test]
+    fn encodes_colors_by_kind() {
+        assert_eq!(color_json(Color::Reset), json!("reset"));
+        assert_eq!(color_json(Color::DarkGray), json!("dark_gray"));
+        assert_eq!(color_json(Color::Rgb(1, 2, 255)), json!("#0102ff"));
+        assert_eq!(color_json(Color::Indexed(0)), json!(0));
+    }
+}
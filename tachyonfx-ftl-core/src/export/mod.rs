@@ -0,0 +1,67 @@
+//! Exporters turning the frames of a [Headless](crate::headless::Headless) run
+//! into animations.
+
+use ratatui::buffer::Cell;
+use ratatui::style::{Color, Modifier};
+use tachyonfx::{Duration, ToRgbComponents};
+use crate::gruvbox::Gruvbox;
+use crate::headless::Frame;
+
+#[cfg(feature = "raster-export")]
+pub mod apng;
+pub mod asciicast;
+#[cfg(feature = "raster-export")]
+pub mod gif;
+pub mod json;
+#[cfg(feature = "raster-export")]
+pub mod raster;
+pub mod svg;
+
+/// foreground of cells without a color of their own
+pub const DEFAULT_FG: Color = Gruvbox::light1();
+/// background of cells without a color of their own
+pub const DEFAULT_BG: Color = Gruvbox::dark0_hard();
+
+/// Resolves the colors of `cell` as rendered by a terminal: `Reset` falls back
+/// to the defaults and reversed cells swap foreground and background.
+pub fn cell_colors(cell: &Cell) -> ((u8, u8, u8), (u8, u8, u8)) {
+    let resolve = |color: Color, default: Color| match color {
+        Color::Reset => default.to_rgb(),
+        color        => color.to_rgb(),
+    };
+
+    let fg = resolve(cell.fg, DEFAULT_FG);
+    let bg = resolve(cell.bg, DEFAULT_BG);
+    match cell.modifier.contains(Modifier::REVERSED) {
+        true  => (bg, fg),
+        false => (fg, bg),
+    }
+}
+
+/// Pairs each frame with how long it's shown. Frames identical to their
+/// predecessor are dropped and the predecessor is held instead; the last frame
+/// is held for one frame step.
+pub(crate) fn frame_durations(frames: &[Frame]) -> Vec<(&Frame, Duration)> {
+    let mut distinct: Vec<&Frame> = Vec::new();
+    for frame in frames {
+        if distinct.last().is_none_or(|last| last.buffer != frame.buffer) {
+            distinct.push(frame);
+        }
+    }
+
+    let frame_step = match frames {
+        [.., a, b] => b.timestamp - a.timestamp,
+        _          => Duration::from_millis(1000),
+    };
+    let end = frames.last().map(|f| f.timestamp + frame_step).unwrap_or_default();
+
+    let next_timestamps = distinct.iter()
+        .skip(1)
+        .map(|f| f.timestamp)
+        .chain(std::iter::once(end));
+
+    distinct.iter()
+        .zip(next_timestamps)
+        .map(|(frame, next)| (*frame, next - frame.timestamp))
+        .collect()
+}
@@ -0,0 +1,463 @@
+//! Rasterizes buffers into RGB pixels, for the image exporters.
+//!
+//! Text is drawn with the embedded-graphics bitmap fonts, which only cover
+//! ISO 8859-1. Block elements, braille and the common box-drawing characters
+//! are drawn procedurally, so borders and effects such as `dissolve` still line
+//! up across cells; other characters fall back to the font's replacement glyph.
+
+use embedded_graphics::image::GetPixel;
+use embedded_graphics::mono_font::iso_8859_1::*;
+use embedded_graphics::mono_font::MonoFont;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::{OriginDimensions, Point};
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::layout::Rect;
+use ratatui::style::Modifier;
+use crate::export::cell_colors;
+
+/// default cell size, in pixels
+pub const DEFAULT_CELL_SIZE: (u32, u32) = (9, 18);
+
+/// A font with its bold and italic variants, where available.
+struct FontFamily {
+    regular: &'static MonoFont<'static>,
+    bold: Option<&'static MonoFont<'static>>,
+    italic: Option<&'static MonoFont<'static>>,
+}
+
+/// ordered by size, smallest first
+const FONTS: &[FontFamily] = &[
+    FontFamily { regular: &FONT_4X6,  bold: None,                  italic: None },
+    FontFamily { regular: &FONT_5X8,  bold: None,                  italic: None },
+    FontFamily { regular: &FONT_6X10, bold: None,                  italic: None },
+    FontFamily { regular: &FONT_6X13, bold: Some(&FONT_6X13_BOLD), italic: Some(&FONT_6X13_ITALIC) },
+    FontFamily { regular: &FONT_7X13, bold: Some(&FONT_7X13_BOLD), italic: Some(&FONT_7X13_ITALIC) },
+    FontFamily { regular: &FONT_7X14, bold: Some(&FONT_7X14_BOLD), italic: None },
+    FontFamily { regular: &FONT_8X13, bold: Some(&FONT_8X13_BOLD), italic: Some(&FONT_8X13_ITALIC) },
+    FontFamily { regular: &FONT_9X15, bold: Some(&FONT_9X15_BOLD), italic: None },
+    FontFamily { regular: &FONT_9X18, bold: Some(&FONT_9X18_BOLD), italic: None },
+    FontFamily { regular: &FONT_10X20, bold: None,                 italic: None },
+];
+
+/// Draws buffers with a fixed cell size, using the largest font that fits.
+#[derive(Clone, Copy)]
+pub struct Rasterizer {
+    cell_width: u32,
+    cell_height: u32,
+    fonts: &'static FontFamily,
+}
+
+impl Default for Rasterizer {
+    fn default() -> Self {
+        Self::new(DEFAULT_CELL_SIZE.0, DEFAULT_CELL_SIZE.1)
+    }
+}
+
+impl Rasterizer {
+    /// Cells smaller than the smallest font clip the glyphs.
+    pub fn new(cell_width: u32, cell_height: u32) -> Self {
+        let cell_width = cell_width.max(1);
+        let cell_height = cell_height.max(1);
+
+        let fonts = FONTS.iter()
+            .rev()
+            .find(|f| {
+                let size = f.regular.character_size;
+                size.width <= cell_width && size.height <= cell_height
+            })
+            .unwrap_or(&FONTS[0]);
+
+        Self { cell_width, cell_height, fonts }
+    }
+
+    pub fn cell_size(&self) -> (u32, u32) {
+        (self.cell_width, self.cell_height)
+    }
+
+    /// size of the image for a buffer covering `area`
+    pub fn image_size(&self, area: Rect) -> (u32, u32) {
+        (area.width as u32 * self.cell_width, area.height as u32 * self.cell_height)
+    }
+
+    /// Draws `buffer` as RGB pixels, three bytes per pixel in row-major order.
+    pub fn rasterize(&self, buffer: &Buffer) -> Vec<u8> {
+        let (width, height) = self.image_size(buffer.area);
+        let mut pixels = vec![0; width as usize * height as usize * 3];
+
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                let cell = &buffer.content[buffer.index_of(buffer.area.x + x, buffer.area.y + y)];
+                let mut painter = Painter {
+                    pixels: &mut pixels,
+                    stride: width,
+                    x: x as u32 * self.cell_width,
+                    y: y as u32 * self.cell_height,
+                    width: self.cell_width,
+                    height: self.cell_height,
+                    color: (0, 0, 0),
+                };
+                self.draw_cell(cell, &mut painter);
+            }
+        }
+
+        pixels
+    }
+
+    fn draw_cell(&self, cell: &Cell, painter: &mut Painter) {
+        let (fg, bg) = cell_colors(cell);
+        let fg = match cell.modifier.contains(Modifier::DIM) {
+            true  => blend(fg, bg),
+            false => fg,
+        };
+
+        painter.color = bg;
+        painter.fill(0, 0, painter.width as i32, painter.height as i32);
+
+        if cell.modifier.contains(Modifier::HIDDEN) {
+            return;
+        }
+
+        painter.color = fg;
+        let c = cell.symbol().chars().next().unwrap_or(' ');
+        if c != ' ' && !draw_block(c, painter) && !draw_braille(c, painter) && !draw_box(c, painter) {
+            self.draw_glyph(c, cell.modifier, painter);
+        }
+
+        let font = self.fonts.regular;
+        let top = self.font_top(font);
+        if cell.modifier.contains(Modifier::UNDERLINED) {
+            let offset = top + font.underline.offset as i32;
+            painter.fill(0, offset, painter.width as i32, offset + font.underline.height as i32);
+        }
+        if cell.modifier.contains(Modifier::CROSSED_OUT) {
+            let offset = top + font.strikethrough.offset as i32;
+            painter.fill(0, offset, painter.width as i32, offset + font.strikethrough.height as i32);
+        }
+    }
+
+    fn draw_glyph(&self, c: char, modifier: Modifier, painter: &mut Painter) {
+        let font = match (modifier.contains(Modifier::BOLD), modifier.contains(Modifier::ITALIC)) {
+            (true, _) => self.fonts.bold,
+            (_, true) => self.fonts.italic,
+            _         => None,
+        }.unwrap_or(self.fonts.regular);
+
+        let size = font.character_size;
+        let per_row = font.image.size().width / size.width;
+        let index = font.glyph_mapping.index(c) as u32;
+        let glyph_x = (index % per_row * size.width) as i32;
+        let glyph_y = (index / per_row * size.height) as i32;
+
+        let left = (painter.width as i32 - size.width as i32) / 2;
+        let top = self.font_top(font);
+        for y in 0..size.height as i32 {
+            for x in 0..size.width as i32 {
+                let pixel = font.image.pixel(Point::new(glyph_x + x, glyph_y + y));
+                if pixel == Some(BinaryColor::On) {
+                    painter.fill(left + x, top + y, left + x + 1, top + y + 1);
+                }
+            }
+        }
+    }
+
+    /// vertical offset of the glyphs within the cell; glyphs are centered
+    fn font_top(&self, font: &MonoFont) -> i32 {
+        (self.cell_height as i32 - font.character_size.height as i32) / 2
+    }
+}
+
+/// Fills rectangles within a single cell, clipped to the cell.
+struct Painter<'a> {
+    pixels: &'a mut [u8],
+    stride: u32,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    color: (u8, u8, u8),
+}
+
+impl Painter<'_> {
+    /// fills `x0..x1` by `y0..y1`, relative to the cell
+    fn fill(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        let clamp_x = |x: i32| x.clamp(0, self.width as i32) as u32;
+        let clamp_y = |y: i32| y.clamp(0, self.height as i32) as u32;
+        let (r, g, b) = self.color;
+
+        for y in clamp_y(y0)..clamp_y(y1) {
+            for x in clamp_x(x0)..clamp_x(x1) {
+                let offset = (((self.y + y) * self.stride + self.x + x) * 3) as usize;
+                self.pixels[offset..offset + 3].copy_from_slice(&[r, g, b]);
+            }
+        }
+    }
+
+    fn set(&mut self, x: i32, y: i32) {
+        self.fill(x, y, x + 1, y + 1);
+    }
+}
+
+/// block elements, U+2580 to U+259F
+fn draw_block(c: char, p: &mut Painter) -> bool {
+    let (w, h) = (p.width as i32, p.height as i32);
+    let eighths = |n: i32, size: i32| (size * n + 4) / 8;
+
+    match c as u32 {
+        0x2580 => p.fill(0, 0, w, h / 2),
+        n @ 0x2581..=0x2588 => p.fill(0, h - eighths(n as i32 - 0x2580, h), w, h),
+        n @ 0x2589..=0x258F => p.fill(0, 0, eighths(0x2590 - n as i32, w), h),
+        0x2590 => p.fill(w / 2, 0, w, h),
+        n @ 0x2591..=0x2593 => {
+            for y in 0..h {
+                for x in 0..w {
+                    let lit = match n {
+                        0x2591 => x % 2 == 0 && y % 2 == 0,
+                        0x2592 => (x + y) % 2 == 0,
+                        _      => x % 2 == 0 || y % 2 == 0,
+                    };
+                    if lit {
+                        p.set(x, y);
+                    }
+                }
+            }
+        }
+        0x2594 => p.fill(0, 0, w, eighths(1, h).max(1)),
+        0x2595 => p.fill(w - eighths(1, w).max(1), 0, w, h),
+        n @ 0x2596..=0x259F => {
+            // upper left, upper right, lower left, lower right
+            let quadrants: u8 = [4, 8, 1, 13, 9, 7, 11, 2, 6, 14][n as usize - 0x2596];
+            let (cx, cy) = (w / 2, h / 2);
+            if quadrants & 1 != 0 { p.fill(0, 0, cx, cy) }
+            if quadrants & 2 != 0 { p.fill(cx, 0, w, cy) }
+            if quadrants & 4 != 0 { p.fill(0, cy, cx, h) }
+            if quadrants & 8 != 0 { p.fill(cx, cy, w, h) }
+        }
+        _ => return false,
+    }
+
+    true
+}
+
+/// braille patterns, U+2800 to U+28FF; a 2x4 grid of dots
+fn draw_braille(c: char, p: &mut Painter) -> bool {
+    let dots = match c as u32 {
+        n @ 0x2800..=0x28FF => n - 0x2800,
+        _ => return false,
+    };
+
+    // bit order of the dots, by column and row
+    const DOTS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+
+    let (w, h) = (p.width as i32, p.height as i32);
+    let size = (w / 4).max(1);
+    for (col, bits) in DOTS.iter().enumerate() {
+        for (row, bit) in bits.iter().enumerate() {
+            if dots & bit != 0 {
+                let x = w * (2 * col as i32 + 1) / 4 - size / 2;
+                let y = h * (2 * row as i32 + 1) / 8 - size / 2;
+                p.fill(x, y, x + size, y + size);
+            }
+        }
+    }
+
+    true
+}
+
+/// line weights of box-drawing characters: up, right, down and left; 1 is a
+/// light line, 2 a heavy line and 3 a double line. Dashed lines are drawn solid
+/// and rounded corners square.
+fn line_weights(c: char) -> Option<[u8; 4]> {
+    let weights = match c {
+        '─' | '┄' | '┈' | '╌' => [0, 1, 0, 1],
+        '━' | '┅' | '┉' | '╍' => [0, 2, 0, 2],
+        '│' | '┆' | '┊' | '╎' => [1, 0, 1, 0],
+        '┃' | '┇' | '┋' | '╏' => [2, 0, 2, 0],
+        '┌' | '╭' => [0, 1, 1, 0],
+        '┐' | '╮' => [0, 0, 1, 1],
+        '└' | '╰' => [1, 1, 0, 0],
+        '┘' | '╯' => [1, 0, 0, 1],
+        '┏' => [0, 2, 2, 0],
+        '┓' => [0, 0, 2, 2],
+        '┗' => [2, 2, 0, 0],
+        '┛' => [2, 0, 0, 2],
+        '├' => [1, 1, 1, 0],
+        '┤' => [1, 0, 1, 1],
+        '┬' => [0, 1, 1, 1],
+        '┴' => [1, 1, 0, 1],
+        '┼' => [1, 1, 1, 1],
+        '┣' => [2, 2, 2, 0],
+        '┫' => [2, 0, 2, 2],
+        '┳' => [0, 2, 2, 2],
+        '┻' => [2, 2, 0, 2],
+        '╋' => [2, 2, 2, 2],
+        '═' => [0, 3, 0, 3],
+        '║' => [3, 0, 3, 0],
+        '╔' => [0, 3, 3, 0],
+        '╗' => [0, 0, 3, 3],
+        '╚' => [3, 3, 0, 0],
+        '╝' => [3, 0, 0, 3],
+        '╠' => [3, 3, 3, 0],
+        '╣' => [3, 0, 3, 3],
+        '╦' => [0, 3, 3, 3],
+        '╩' => [3, 3, 0, 3],
+        '╬' => [3, 3, 3, 3],
+        '╴' => [0, 0, 0, 1],
+        '╵' => [1, 0, 0, 0],
+        '╶' => [0, 1, 0, 0],
+        '╷' => [0, 0, 1, 0],
+        '╸' => [0, 0, 0, 2],
+        '╹' => [2, 0, 0, 0],
+        '╺' => [0, 2, 0, 0],
+        '╻' => [0, 0, 2, 0],
+        _ => return None,
+    };
+
+    Some(weights)
+}
+
+fn draw_box(c: char, p: &mut Painter) -> bool {
+    let Some([up, right, down, left]) = line_weights(c) else { return false };
+
+    let (w, h) = (p.width as i32, p.height as i32);
+    let (cx, cy) = (w / 2, h / 2);
+    let t = (w.min(h) / 8).max(1);
+
+    // bands of a line across the center, as offsets from the center
+    let bands = |weight: u8| -> Vec<(i32, i32)> {
+        match weight {
+            0 => vec![],
+            1 => vec![(-t / 2, t - t / 2)],
+            2 => vec![(-t, t)],
+            _ => vec![(-t - t / 2, -t / 2), (t - t / 2, 2 * t - t / 2)],
+        }
+    };
+    // arms reach across the perpendicular lines, or to the center on their own
+    let reach = |a: u8, b: u8, own: u8| {
+        let spans = [bands(a), bands(b)].concat();
+        let spans = if spans.is_empty() { bands(own) } else { spans };
+        let lo = spans.iter().map(|s| s.0).min().unwrap_or(0);
+        let hi = spans.iter().map(|s| s.1).max().unwrap_or(0);
+        (lo, hi)
+    };
+
+    for (y0, y1) in bands(left) {
+        p.fill(0, cy + y0, cx + reach(up, down, left).1, cy + y1);
+    }
+    for (y0, y1) in bands(right) {
+        p.fill(cx + reach(up, down, right).0, cy + y0, w, cy + y1);
+    }
+    for (x0, x1) in bands(up) {
+        p.fill(cx + x0, 0, cx + x1, cy + reach(left, right, up).1);
+    }
+    for (x0, x1) in bands(down) {
+        p.fill(cx + x0, cy + reach(left, right, down).0, cx + x1, h);
+    }
+
+    true
+}
+
+/// halfway between `a` and `b`, used for dimmed text
+fn blend(a: (u8, u8, u8), b: (u8, u8, u8)) -> (u8, u8, u8) {
+    let mix = |a: u8, b: u8| ((a as u16 + b as u16) / 2) as u8;
+    (mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::{Color, Style};
+    use super::*;
+
+    const FG: (u8, u8, u8) = (255, 255, 255);
+    const BG: (u8, u8, u8) = (0, 0, 0);
+
+    /// the pixels of a cell drawn with a white foreground on black, true where
+    /// the foreground is drawn
+    fn draw(symbol: &str, modifier: Modifier) -> Vec<Vec<bool>> {
+        let rasterizer = Rasterizer::default();
+        let (width, _) = rasterizer.cell_size();
+
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 1, 1));
+        let style = Style::new().fg(Color::Rgb(FG.0, FG.1, FG.2)).bg(Color::Rgb(BG.0, BG.1, BG.2));
+        buffer.set_string(0, 0, symbol, style.add_modifier(modifier));
+
+        rasterizer.rasterize(&buffer)
+            .chunks(3)
+            .map(|rgb| match (rgb[0], rgb[1], rgb[2]) {
+                FG => true,
+                BG => false,
+                rgb => panic!("unexpected color {rgb:?}"),
+            })
+            .collect::<Vec<_>>()
+            .chunks(width as usize)
+            .map(<[bool]>::to_vec)
+            .collect()
+    }
+
+    fn lit(pixels: &[Vec<bool>]) -> usize {
+        pixels.iter().flatten().filter(|&&lit| lit).count()
+    }
+
+    #[test]
+    fn picks_the_largest_font_that_fits() {
+        let size = |r: Rasterizer| r.fonts.regular.character_size;
+        assert_eq!(size(Rasterizer::default()), FONT_9X18.character_size);
+        assert_eq!(size(Rasterizer::new(7, 16)), FONT_7X14.character_size);
+        assert_eq!(size(Rasterizer::new(0, 0)), FONT_4X6.character_size);
+        assert_eq!(Rasterizer::new(0, 0).cell_size(), (1, 1));
+        assert_eq!(Rasterizer::default().image_size(Rect::new(4, 2, 10, 3)), (90, 54));
+    }
+
+    #[test]
+    fn draws_glyphs_from_the_font() {
+        let a = draw("A", Modifier::empty());
+        assert!(lit(&a) > 0);
+        assert_eq!(lit(&draw(" ", Modifier::empty())), 0);
+        assert_ne!(draw("B", Modifier::empty()), a);
+        assert!(lit(&draw("A", Modifier::BOLD)) > lit(&a));
+        assert_eq!(lit(&draw("A", Modifier::HIDDEN)), 0);
+
+        // reversed cells swap the colors
+        let reversed = draw("A", Modifier::REVERSED);
+        assert!(reversed.iter().flatten().zip(a.iter().flatten()).all(|(r, a)| r != a));
+    }
+
+    #[test]
+    fn unknown_characters_fall_back_to_the_replacement_glyph() {
+        let replacement = draw("→", Modifier::empty());
+        assert!(lit(&replacement) > 0);
+        assert_eq!(draw("Ω", Modifier::empty()), replacement);
+        assert_ne!(draw("A", Modifier::empty()), replacement);
+    }
+
+    #[test]
+    fn box_drawing_lines_span_the_cell() {
+        let (width, height) = Rasterizer::default().cell_size();
+        let (cx, cy) = (width as usize / 2, height as usize / 2);
+
+        let horizontal = draw("─", Modifier::empty());
+        assert!(horizontal[cy].iter().all(|&lit| lit));
+        assert_eq!(lit(&horizontal), width as usize);
+
+        let vertical = draw("│", Modifier::empty());
+        assert!(vertical.iter().all(|row| row[cx]));
+        assert_eq!(lit(&vertical), height as usize);
+
+        // heavier lines are thicker; corners reach two edges only
+        assert!(lit(&draw("━", Modifier::empty())) > lit(&horizontal));
+        let corner = draw("┌", Modifier::empty());
+        assert!(corner[cy][width as usize - 1] && corner[height as usize - 1][cx]);
+        assert!(!corner[cy][0] && !corner[0][cx]);
+    }
+
+    #[test]
+    fn block_elements_fill_their_part_of_the_cell() {
+        let (width, height) = Rasterizer::default().cell_size();
+        let (width, height) = (width as usize, height as usize);
+
+        assert_eq!(lit(&draw("█", Modifier::empty())), width * height);
+        let upper = draw("▀", Modifier::empty());
+        assert!(upper[..height / 2].iter().flatten().all(|&lit| lit));
+        assert!(upper[height / 2..].iter().flatten().all(|&lit| !lit));
+        assert_eq!(lit(&draw("⣿", Modifier::empty())), 8 * (width / 4) * (width / 4));
+    }
+}
@@ -0,0 +1,261 @@
+//! Writes frames as a single animated SVG. All frames are laid out side by side
+//! and a CSS animation steps through them, so the image plays anywhere SVG is
+//! rendered, e.g. in a GitHub README.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::style::Modifier;
+use ratatui::text::Span;
+use tachyonfx::ToRgbComponents;
+use crate::export::{cell_colors, frame_durations, DEFAULT_BG};
+use crate::headless::Frame;
+
+const FONT_SIZE: f32 = 14.0;
+const CELL_WIDTH: f32 = 8.4;
+const CELL_HEIGHT: f32 = 17.0;
+/// distance from the top of a cell to the text baseline
+const BASELINE: f32 = 13.0;
+
+/// Writes `frames` as an animated SVG, looping forever. Each frame is shown
+/// until the timestamp of the next; the last frame is held for one frame step.
+pub fn write_svg(frames: &[Frame], mut out: impl Write) -> io::Result<()> {
+    let frames = frame_durations(frames);
+    let Some((first, _)) = frames.first() else { return Ok(()) };
+
+    let area = first.buffer.area;
+    let width = area.width as f32 * CELL_WIDTH;
+    let height = area.height as f32 * CELL_HEIGHT;
+
+    let mut palette = Palette::default();
+    let mut body = String::new();
+    for (i, (frame, _)) in frames.iter().enumerate() {
+        let x = i as f32 * width;
+        let _ = writeln!(body, r#"<g transform="translate({x:.1},0)">"#);
+        write_frame(&frame.buffer, &mut palette, &mut body);
+        body.push_str("</g>\n");
+    }
+
+    let total = frames.iter()
+        .map(|(_, shown)| shown.as_millis())
+        .sum::<u32>()
+        .max(1);
+    let (r, g, b) = DEFAULT_BG.to_rgb();
+
+    writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.1}" height="{height:.1}" viewBox="0 0 {width:.1} {height:.1}">"#)?;
+    writeln!(out, "<style>")?;
+    writeln!(out, "text {{ font-family: ui-monospace, Menlo, Consolas, monospace; font-size: {FONT_SIZE}px; white-space: pre; }}")?;
+    writeln!(out, ".b {{ font-weight: bold; }} .i {{ font-style: italic; }} .d {{ opacity: 0.5; }}")?;
+    writeln!(out, ".u {{ text-decoration: underline; }} .s {{ text-decoration: line-through; }}")?;
+    palette.write_css(&mut out)?;
+    writeln!(out, "@keyframes play {{")?;
+    let first_timestamp = first.timestamp;
+    for (i, (frame, _)) in frames.iter().enumerate() {
+        let t = frame.timestamp - first_timestamp;
+        let percent = t.as_millis() as f32 / total as f32 * 100.0;
+        writeln!(out, "  {percent:.3}% {{ transform: translateX({:.1}px); }}", -(i as f32) * width)?;
+    }
+    writeln!(out, "  100% {{ transform: translateX({:.1}px); }}", -((frames.len() - 1) as f32) * width)?;
+    writeln!(out, "}}")?;
+    writeln!(out, ".frames {{ animation: play {total}ms steps(1, end) infinite; }}")?;
+    writeln!(out, "</style>")?;
+    writeln!(out, r#"<rect width="100%" height="100%" fill="rgb({r},{g},{b})"/>"#)?;
+    writeln!(out, r#"<g class="frames">"#)?;
+    out.write_all(body.as_bytes())?;
+    writeln!(out, "</g>")?;
+    writeln!(out, "</svg>")
+}
+
+/// backgrounds as rects, followed by the text; one text element per row with a
+/// tspan for each run of equally styled cells
+fn write_frame(buf: &Buffer, palette: &mut Palette, svg: &mut String) {
+    let area = buf.area;
+    let rows = (area.top()..area.bottom()).map(|y| {
+        (area.left()..area.right()).map(move |x| &buf[(x, y)]).collect::<Vec<_>>()
+    });
+
+    for (row, cells) in rows.enumerate() {
+        let y = row as f32 * CELL_HEIGHT;
+
+        for (start, run) in runs(cells.iter().copied().enumerate(), |cell| cell_colors(cell).1) {
+            let bg = cell_colors(run[0]).1;
+            if bg == DEFAULT_BG.to_rgb() {
+                continue;
+            }
+
+            let _ = writeln!(svg,
+                r#"<rect x="{:.1}" y="{y:.1}" width="{:.1}" height="{CELL_HEIGHT}" class="{}"/>"#,
+                start as f32 * CELL_WIDTH,
+                run.len() as f32 * CELL_WIDTH,
+                palette.class(bg),
+            );
+        }
+
+        let mut text = String::new();
+        for (start, run) in runs(uncovered(&cells), text_style) {
+            let content: String = run.iter().map(|c| c.symbol()).collect();
+            if content.trim().is_empty() || run[0].modifier.contains(Modifier::HIDDEN) {
+                continue;
+            }
+
+            let mut class = palette.class(cell_colors(run[0]).0);
+            let modifier = run[0].modifier;
+            [(Modifier::BOLD, " b"), (Modifier::ITALIC, " i"), (Modifier::DIM, " d"),
+             (Modifier::UNDERLINED, " u"), (Modifier::CROSSED_OUT, " s")]
+                .iter()
+                .filter(|(m, _)| modifier.contains(*m))
+                .for_each(|(_, c)| class.push_str(c));
+
+            let _ = write!(text,
+                r#"<tspan x="{:.1}" class="{class}">{}</tspan>"#,
+                start as f32 * CELL_WIDTH,
+                escape(&content),
+            );
+        }
+
+        if !text.is_empty() {
+            let _ = writeln!(svg, r#"<text y="{:.1}">{text}</text>"#, y + BASELINE);
+        }
+    }
+}
+
+fn text_style(cell: &Cell) -> ((u8, u8, u8), Modifier) {
+    (cell_colors(cell).0, cell.modifier)
+}
+
+/// The cells of a row with their columns, leaving out the cells covered by a
+/// wide symbol; text would be pushed a column to the right otherwise.
+fn uncovered<'a>(cells: &[&'a Cell]) -> Vec<(usize, &'a Cell)> {
+    let mut covered_until = 0;
+    cells.iter()
+        .copied()
+        .enumerate()
+        .filter(|&(x, cell)| {
+            let visible = x >= covered_until;
+            if visible {
+                covered_until = x + Span::raw(cell.symbol()).width().max(1);
+            }
+            visible
+        })
+        .collect()
+}
+
+/// groups consecutive cells by `key`, yielding the column of each run
+fn runs<'a, K: PartialEq>(
+    cells: impl IntoIterator<Item = (usize, &'a Cell)>,
+    key: impl Fn(&Cell) -> K,
+) -> Vec<(usize, Vec<&'a Cell>)> {
+    let mut runs: Vec<(usize, Vec<&Cell>)> = Vec::new();
+    for (x, cell) in cells {
+        match runs.last_mut() {
+            Some((_, run)) if key(run[0]) == key(cell) => run.push(cell),
+            _ => runs.push((x, vec![cell])),
+        }
+    }
+
+    runs
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// assigns a css class to each distinct color
+#[derive(Default)]
+struct Palette {
+    classes: HashMap<(u8, u8, u8), usize>,
+}
+
+impl Palette {
+    fn class(&mut self, rgb: (u8, u8, u8)) -> String {
+        let next = self.classes.len();
+        let id = *self.classes.entry(rgb).or_insert(next);
+        format!("c{id}")
+    }
+
+    fn write_css(&self, out: &mut impl Write) -> io::Result<()> {
+        let mut colors: Vec<_> = self.classes.iter().collect();
+        colors.sort_by_key(|(_, id)| **id);
+
+        for ((r, g, b), id) in colors {
+            writeln!(out, ".c{id} {{ fill: #{r:02x}{g:02x}{b:02x}; }}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::layout::Rect;
+    use ratatui::style::{Color, Style};
+    use super::*;
+
+    /// the frame written for a single row of text
+    fn frame_svg(text: &str, styles: &[(u16, u16, Style)]) -> String {
+        let mut buf = Buffer::empty(Rect::new(0, 0, Span::raw(text).width() as u16, 1));
+        buf.set_string(0, 0, text, Style::default());
+        for &(x, width, style) in styles {
+            buf.set_style(Rect::new(x, 0, width, 1), style);
+        }
+
+        let mut svg = String::new();
+        write_frame(&buf, &mut Palette::default(), &mut svg);
+        svg
+    }
+
+    fn tspans(svg: &str) -> Vec<&str> {
+        svg.split("<tspan ").skip(1)
+            .map(|tspan| tspan.split_once("</tspan>").map_or(tspan, |(t, _)| t))
+            .collect()
+    }
+
+    #[test]
+    fn groups_equally_styled_cells_into_runs() {
+        let svg = frame_svg("abc def", &[(4, 3, Style::new().fg(Color::Red))]);
+
+        assert_eq!(tspans(&svg), [
+            r#"x="0.0" class="c0">abc "#,
+            r#"x="33.6" class="c1">def"#,
+        ]);
+    }
+
+    #[test]
+    fn skips_the_default_background() {
+        let svg = frame_svg("abcd", &[(1, 2, Style::new().bg(Color::Rgb(255, 0, 0)))]);
+
+        let rects: Vec<_> = svg.lines().filter(|line| line.starts_with("<rect")).collect();
+        assert_eq!(rects, [r#"<rect x="8.4" y="0.0" width="16.8" height="17" class="c0"/>"#]);
+    }
+
+    #[test]
+    fn leaves_out_hidden_and_blank_text() {
+        let svg = frame_svg("ab  cd", &[(4, 2, Style::new().add_modifier(Modifier::HIDDEN))]);
+
+        assert_eq!(tspans(&svg), [r#"x="0.0" class="c0">ab  "#]);
+        assert_eq!(frame_svg("   ", &[]), "");
+    }
+
+    #[test]
+    fn adds_a_class_per_modifier() {
+        let bold_italic = Style::new().add_modifier(Modifier::BOLD | Modifier::ITALIC);
+        let svg = frame_svg("ab", &[(0, 1, bold_italic), (1, 1, Style::new().add_modifier(Modifier::CROSSED_OUT))]);
+
+        assert_eq!(tspans(&svg), [r#"x="0.0" class="c0 b i">a"#, r#"x="8.4" class="c0 s">b"#]);
+    }
+
+    #[test]
+    fn escapes_markup() {
+        assert_eq!(tspans(&frame_svg("<a&b>", &[])), [r#"x="0.0" class="c0">&lt;a&amp;b&gt;"#]);
+    }
+
+    #[test]
+    fn wide_characters_cover_the_next_cell() {
+        let svg = frame_svg("a世b", &[(3, 1, Style::new().fg(Color::Red))]);
+
+        assert_eq!(tspans(&svg), [r#"x="0.0" class="c0">a世"#, r#"x="25.2" class="c1">b"#]);
+    }
+}
@@ -0,0 +1,568 @@
+//! Pretty-printing of effect DSL source, in the style of rustfmt. Calls and
+//! lists that don't fit on a line get one element per line and a trailing
+//! comma; the effects of `fx::parallel` and `fx::sequence` always do, so that
+//! nested effects read as a tree. Comments and single blank lines are kept.
+
+use crate::diagnostic::{check_brackets, Diagnostic};
+use crate::lexer::{closing_bracket, tokenize, Token, TokenKind};
+
+const MAX_WIDTH: usize = 100;
+const INDENT: usize = 4;
+
+/// Reformats the source; fails when the brackets don't balance, as the
+/// structure of the source can't be known then.
+pub fn format_dsl(source: &str) -> Result<String, Vec<Diagnostic>> {
+    let diagnostics = check_brackets(source);
+    if !diagnostics.is_empty() {
+        return Err(diagnostics);
+    }
+
+    let tokens = merge_path_separators(source, tokenize(source));
+    let mut parser = Parser { source, tokens: &tokens, pos: 0, prev_end: 0 };
+    let statements = parser.items(';');
+
+    let mut printer = Printer::default();
+    for (i, statement) in statements.iter().enumerate() {
+        let separator = if statement.separated { ";" } else { "" };
+        printer.item(statement, 0, separator, i == 0);
+    }
+    if !printer.out.is_empty() {
+        printer.out.push('\n');
+    }
+
+    Ok(printer.out)
+}
+
+enum Node<'a> {
+    Atom(Token<'a>),
+    Group(Group<'a>),
+}
+
+/// A bracketed, comma-separated list: arguments, arrays, tuples and struct
+/// literals.
+struct Group<'a> {
+    open: &'a str,
+    close: &'a str,
+    items: Vec<Item<'a>>,
+    /// set for the effects of `fx::parallel` and `fx::sequence`
+    always_break: bool,
+}
+
+/// A statement, or an element of a [Group], with its comments.
+#[derive(Default)]
+struct Item<'a> {
+    /// comments on the lines before the item, each with whether a blank line
+    /// precedes it
+    leading: Vec<(Token<'a>, bool)>,
+    nodes: Vec<Node<'a>>,
+    /// a comment following the item on the same line
+    trailing: Option<Token<'a>>,
+    blank_line_before: bool,
+    /// whether the item is followed by its separator
+    separated: bool,
+}
+
+impl Item<'_> {
+    fn is_empty(&self) -> bool {
+        self.leading.is_empty() && self.nodes.is_empty()
+    }
+}
+
+struct Parser<'a, 't> {
+    source: &'a str,
+    tokens: &'t [Token<'a>],
+    pos: usize,
+    /// end of the last token consumed
+    prev_end: usize,
+}
+
+impl<'a> Parser<'a, '_> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.peek()?;
+        self.pos += 1;
+        self.prev_end = token.end();
+        Some(token)
+    }
+
+    /// whether the source between the last token and `token` holds a blank line
+    fn blank_line_before(&self, token: &Token) -> bool {
+        self.source[self.prev_end..token.start].matches('\n').count() > 1
+    }
+
+    fn same_line(&self, token: &Token) -> bool {
+        !self.source[self.prev_end..token.start].contains('\n')
+    }
+
+    /// Whether `token`, just after a separator, is a comment on the same line
+    /// that ends it; a block comment followed by the next item leads that item.
+    fn is_trailing_comment(&self, token: &Token) -> bool {
+        let ends_line = match self.tokens.get(self.pos + 1) {
+            Some(next) if !matches!(next.kind, TokenKind::Close(_)) => {
+                self.source[token.end()..next.start].contains('\n')
+            }
+            _ => true,
+        };
+
+        is_comment(token) && self.same_line(token) && (token.kind == TokenKind::LineComment || ends_line)
+    }
+
+    /// Items up to the end of the source or the closing bracket of the
+    /// enclosing group, which is left unconsumed.
+    fn items(&mut self, separator: char) -> Vec<Item<'a>> {
+        let mut items = Vec::new();
+        let mut item = Item::default();
+
+        while let Some(token) = self.peek() {
+            let blank_line = self.blank_line_before(&token);
+            let comment = is_comment(&token);
+
+            match token.kind {
+                TokenKind::Close(_) => break,
+                TokenKind::Punct(c) if c == separator => {
+                    self.next();
+                    item.separated = true;
+                    item.trailing = self.peek()
+                        .filter(|t| self.is_trailing_comment(t))
+                        .and_then(|_| self.next());
+                    mark_effect_lists(&mut item.nodes);
+                    items.push(std::mem::take(&mut item));
+                }
+                _ if comment && item.nodes.is_empty() => {
+                    self.next();
+                    item.leading.push((token, blank_line));
+                }
+                _ if comment && self.only_comments_remain(self.pos + 1) => {
+                    // a comment after the last item, which has no separator
+                    let same_line = self.same_line(&token);
+                    self.next();
+                    if same_line && item.trailing.is_none() {
+                        item.trailing = Some(token);
+                    } else {
+                        mark_effect_lists(&mut item.nodes);
+                        items.push(std::mem::take(&mut item));
+                        item.leading.push((token, blank_line));
+                    }
+                }
+                TokenKind::Open(c) => {
+                    if item.nodes.is_empty() {
+                        item.blank_line_before = blank_line;
+                    }
+                    self.next();
+                    let items = self.items(',');
+                    let close = self.next().expect("brackets are balanced");
+                    debug_assert_eq!(close.kind, TokenKind::Close(closing_bracket(c)));
+
+                    item.nodes.push(Node::Group(Group {
+                        open: token.text,
+                        close: close.text,
+                        items,
+                        always_break: false,
+                    }));
+                }
+                _ => {
+                    if item.nodes.is_empty() {
+                        item.blank_line_before = blank_line;
+                    }
+                    self.next();
+                    item.nodes.push(Node::Atom(token));
+                }
+            }
+        }
+
+        if !item.is_empty() {
+            mark_effect_lists(&mut item.nodes);
+            items.push(item);
+        }
+
+        items
+    }
+
+    /// whether only comments remain before the end of the group or source
+    fn only_comments_remain(&self, from: usize) -> bool {
+        self.tokens[from..].iter()
+            .find(|t| !is_comment(t))
+            .is_none_or(|t| matches!(t.kind, TokenKind::Close(_)))
+    }
+}
+
+/// Joins the two `:` tokens of each `::`, which the lexer keeps apart.
+fn merge_path_separators<'a>(source: &'a str, tokens: Vec<Token<'a>>) -> Vec<Token<'a>> {
+    let mut merged: Vec<Token<'a>> = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        match merged.last_mut() {
+            Some(prev) if prev.text == ":" && token.text == ":" && prev.end() == token.start => {
+                prev.text = &source[prev.start..token.end()];
+            }
+            _ => merged.push(token),
+        }
+    }
+
+    merged
+}
+
+/// Marks the effect arrays of `parallel` and `sequence` calls with more than
+/// one effect to always break.
+fn mark_effect_lists(nodes: &mut [Node]) {
+    for i in 1..nodes.len() {
+        let is_combinator = matches!(&nodes[i - 1], Node::Atom(t) if matches!(t.text, "parallel" | "sequence"));
+        let Node::Group(call) = &mut nodes[i] else { continue };
+        if !is_combinator || call.open != "(" {
+            continue;
+        }
+
+        if let [item] = call.items.as_mut_slice() {
+            if let Some(Node::Group(effects)) = item.nodes.last_mut() {
+                effects.always_break = effects.items.iter().filter(|i| !i.nodes.is_empty()).count() > 1;
+            }
+        }
+    }
+}
+
+fn is_comment(token: &Token) -> bool {
+    matches!(token.kind, TokenKind::LineComment | TokenKind::BlockComment)
+}
+
+/// the nodes on a single line, unless they hold line comments or a group
+/// that must break
+fn flat_nodes(nodes: &[Node]) -> Option<String> {
+    let mut out = String::new();
+    for (i, node) in nodes.iter().enumerate() {
+        if i > 0 && space_before(nodes, i) {
+            out.push(' ');
+        }
+        match node {
+            Node::Atom(token) if token.kind == TokenKind::LineComment => return None,
+            Node::Atom(token) => out.push_str(token.text),
+            Node::Group(group) => out.push_str(&flat_group(group)?),
+        }
+    }
+
+    Some(out)
+}
+
+fn flat_group(group: &Group) -> Option<String> {
+    let has_comments = group.items.iter().any(|i| !i.leading.is_empty() || i.trailing.is_some());
+    if group.always_break || has_comments {
+        return None;
+    }
+
+    let items = group.items.iter()
+        .map(|i| flat_nodes(&i.nodes))
+        .collect::<Option<Vec<_>>>()?
+        .join(", ");
+    let padding = if group.open == "{" && !items.is_empty() { " " } else { "" };
+
+    Some(format!("{}{padding}{items}{padding}{}", group.open, group.close))
+}
+
+/// the first and last token kind and text of a node
+fn first<'a>(node: &Node<'a>) -> (TokenKind, &'a str) {
+    match node {
+        Node::Atom(token)  => (token.kind, token.text),
+        Node::Group(group) => (TokenKind::Open(group.open.chars().next().unwrap_or('(')), group.open),
+    }
+}
+
+fn last<'a>(node: &Node<'a>) -> (TokenKind, &'a str) {
+    match node {
+        Node::Atom(token)  => (token.kind, token.text),
+        Node::Group(group) => (TokenKind::Close(group.close.chars().next().unwrap_or(')')), group.close),
+    }
+}
+
+/// Whether a space separates `nodes[i]` from the node before it.
+fn space_before(nodes: &[Node], i: usize) -> bool {
+    let (prev, prev_text) = last(&nodes[i - 1]);
+    let (next, next_text) = first(&nodes[i]);
+
+    match (prev, next) {
+        (TokenKind::LineComment | TokenKind::BlockComment, _) => true,
+        (_, TokenKind::LineComment | TokenKind::BlockComment) => true,
+        _ if prev_text == "::" || next_text == "::"          => false,
+        (TokenKind::Punct('.' | '!'), _)                      => false,
+        (_, TokenKind::Punct('.' | '!' | ',' | ';' | ':'))     => false,
+        (TokenKind::Punct('-' | '&'), _)                      => !is_prefix_operator(nodes, i - 1),
+        (_, TokenKind::Open('(' | '['))                       => !matches!(prev, TokenKind::Ident | TokenKind::Close(_)),
+        _                                                     => true,
+    }
+}
+
+/// whether the `-` or `&` at `nodes[i]` applies to what follows, as in `-1`
+fn is_prefix_operator(nodes: &[Node], i: usize) -> bool {
+    i == 0 || matches!(last(&nodes[i - 1]).0, TokenKind::Punct(_) | TokenKind::Open(_))
+}
+
+/// the width of the nodes before the next group that can break, including its
+/// opening bracket
+fn width_until_break(nodes: &[Node], suffix: usize) -> usize {
+    let mut width = 0;
+    for (i, node) in nodes.iter().enumerate() {
+        if i > 0 && space_before(nodes, i) {
+            width += 1;
+        }
+        match node {
+            Node::Atom(token)  => width += token.text.chars().count(),
+            Node::Group(group) => return width + group.open.len(),
+        }
+    }
+
+    width + suffix
+}
+
+/// The group ending the only element of `group`, when all before it are
+/// atoms: the `[...]` in `(&[...])` or `vec![...]`, or the arguments of a call.
+/// It breaks in place of `group`, as rustfmt does with a last argument.
+fn hugged<'g, 'a>(group: &'g Group<'a>) -> Option<(&'g [Node<'a>], &'g Group<'a>)> {
+    let [item] = group.items.as_slice() else { return None };
+    if !item.leading.is_empty() || item.trailing.is_some() {
+        return None;
+    }
+
+    match item.nodes.split_last()? {
+        (Node::Group(inner), prefix) if inner.open != "{" && prefix.iter().all(|n| matches!(n, Node::Atom(_))) => {
+            Some((prefix, inner))
+        }
+        _ => None,
+    }
+}
+
+/// Splits a method chain into its receiver and calls, e.g. `Style::new()`,
+/// `.fg(Color::Red)` and `.bg(Color::Black)`.
+fn chain<'n, 'a>(nodes: &'n [Node<'a>]) -> Vec<&'n [Node<'a>]> {
+    let is_call = |i: usize| i > 0
+        && matches!(&nodes[i], Node::Atom(t) if t.text == ".")
+        && matches!(nodes.get(i + 1), Some(Node::Atom(t)) if t.kind == TokenKind::Ident);
+
+    let mut links = Vec::new();
+    let mut start = 0;
+    for i in (0..nodes.len()).filter(|&i| is_call(i)) {
+        links.push(&nodes[start..i]);
+        start = i;
+    }
+    links.push(&nodes[start..]);
+
+    links
+}
+
+#[derive(Default)]
+struct Printer {
+    out: String,
+    column: usize,
+}
+
+impl Printer {
+    fn write(&mut self, s: &str) {
+        self.out.push_str(s);
+        self.column = match s.rfind('\n') {
+            Some(i) => s[i + 1..].chars().count(),
+            None    => self.column + s.chars().count(),
+        };
+    }
+
+    fn fits(&self, width: usize) -> bool {
+        self.column + width <= MAX_WIDTH
+    }
+
+    /// starts a new line, unless nothing has been written yet
+    fn start_line(&mut self, indent: usize, blank_line: bool) {
+        if self.out.is_empty() {
+            return;
+        }
+        if blank_line {
+            self.out.push('\n');
+        }
+        self.out.push('\n');
+        self.out.extend(std::iter::repeat_n(' ', indent));
+        self.column = indent;
+    }
+
+    fn comment(&mut self, token: &Token) {
+        self.write(token.text.trim_end());
+    }
+
+    /// Prints an item on a line of its own, followed by `separator`. The first
+    /// item of a list never starts with a blank line.
+    fn item(&mut self, item: &Item, indent: usize, separator: &str, first: bool) {
+        let mut first = first;
+        for (comment, blank_line) in &item.leading {
+            self.start_line(indent, *blank_line && !first);
+            self.comment(comment);
+            first = false;
+        }
+
+        if !item.nodes.is_empty() {
+            self.start_line(indent, item.blank_line_before && !first);
+            self.nodes(&item.nodes, indent, separator.len());
+            self.write(separator);
+        }
+
+        if let Some(comment) = &item.trailing {
+            self.write(" ");
+            self.comment(comment);
+        }
+    }
+
+    /// Prints the nodes on one line if they fit, followed by `suffix`
+    /// characters; otherwise method chains put each call on a line of its
+    /// own, indented unless the receiver spans several lines, and the groups
+    /// among the nodes break.
+    fn nodes(&mut self, nodes: &[Node], indent: usize, suffix: usize) {
+        if let Some(flat) = flat_nodes(nodes) {
+            if self.fits(flat.chars().count() + suffix) {
+                self.write(&flat);
+                return;
+            }
+        }
+
+        let links = chain(nodes);
+        if let [receiver, calls @ ..] = links.as_slice() {
+            if !calls.is_empty() {
+                let start = self.out.len();
+                self.nodes(receiver, indent, 0);
+                let indent = match self.out[start..].contains('\n') {
+                    true  => indent,
+                    false => indent + INDENT,
+                };
+
+                for (i, call) in calls.iter().enumerate() {
+                    self.start_line(indent, false);
+                    let suffix = if i == calls.len() - 1 { suffix } else { 0 };
+                    self.nodes(call, indent, suffix);
+                }
+                return;
+            }
+        }
+
+        for (i, node) in nodes.iter().enumerate() {
+            if i > 0 && space_before(nodes, i) && self.column > indent {
+                self.write(" ");
+            }
+            match node {
+                Node::Atom(token) if token.kind == TokenKind::LineComment => {
+                    self.comment(token);
+                    self.start_line(indent + INDENT, false);
+                }
+                Node::Atom(token)  => self.write(token.text),
+                Node::Group(group) => self.group(group, indent, width_until_break(&nodes[i + 1..], suffix)),
+            }
+        }
+    }
+
+    fn group(&mut self, group: &Group, indent: usize, suffix: usize) {
+        if let Some(flat) = flat_group(group) {
+            if self.fits(flat.chars().count() + suffix) {
+                self.write(&flat);
+                return;
+            }
+        }
+
+        if let Some((prefix, inner)) = hugged(group) {
+            self.write(group.open);
+            if let Some(prefix) = flat_nodes(prefix) {
+                self.write(&prefix);
+            }
+            self.group(inner, indent, suffix + group.close.len());
+            self.write(group.close);
+            return;
+        }
+
+        self.write(group.open);
+        for (i, item) in group.items.iter().enumerate() {
+            self.item(item, indent + INDENT, ",", i == 0);
+        }
+        self.start_line(indent, false);
+        self.write(group.close);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(source: &str) -> String {
+        format_dsl(source).expect("brackets are balanced")
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        let sources = [
+            "let c = Color::from_u32(0xff8000); // orange\n\nfx::fade_to_fg(c, (500, QuadOut))",
+            "fx::parallel(&[fx::sequence(&[fx::dissolve(500), /* then */ fx::coalesce(500)]), fx::sleep(200),])",
+            "fx::sweep_in(Motion::LeftToRight, 10, 0, Color::Rgb(255, 128, 0), (1500, Interpolation::QuadOut)).with_filter(CellFilter::AllOf(vec![CellFilter::Text, CellFilter::FgColor(Color::Red)]))",
+            "fx::sequence(&[\n    // first\n    fx::dissolve(500), // trailing\n\n    fx::coalesce(500)\n    // last\n])",
+        ];
+
+        for source in sources {
+            let formatted = format(source);
+            assert_eq!(format(&formatted), formatted, "{source}");
+        }
+    }
+
+    #[test]
+    fn keeps_leading_and_trailing_comments() {
+        assert_eq!(
+            format("// leading\nfx::dissolve(500)   // trailing"),
+            "// leading\nfx::dissolve(500) // trailing\n",
+        );
+        assert_eq!(
+            format("fx::parallel(&[fx::dissolve(500), // first\n/* second */\nfx::coalesce(500)])"),
+            "fx::parallel(&[\n    fx::dissolve(500), // first\n    /* second */\n    fx::coalesce(500),\n])\n",
+        );
+    }
+
+    #[test]
+    fn inline_block_comments_lead_the_next_item() {
+        assert_eq!(
+            format("fx::sequence(&[fx::fade_to_fg(Color::Red, 500), /* mid */ fx::dissolve(500)])"),
+            "fx::sequence(&[\n    fx::fade_to_fg(Color::Red, 500),\n    /* mid */\n    fx::dissolve(500),\n])\n",
+        );
+        assert_eq!(
+            format("fx::parallel(&[fx::dissolve(500), /* end */\nfx::coalesce(500)])"),
+            "fx::parallel(&[\n    fx::dissolve(500), /* end */\n    fx::coalesce(500),\n])\n",
+        );
+    }
+
+    #[test]
+    fn keeps_comments_at_the_end_of_a_group() {
+        assert_eq!(
+            format("fx::sequence(&[fx::dissolve(500), fx::coalesce(500) // end\n])"),
+            "fx::sequence(&[\n    fx::dissolve(500),\n    fx::coalesce(500), // end\n])\n",
+        );
+        assert_eq!(
+            format("fx::sequence(&[\nfx::dissolve(500),\n// last\n])"),
+            "fx::sequence(&[\n    fx::dissolve(500),\n    // last\n])\n",
+        );
+    }
+
+    #[test]
+    fn trailing_commas_only_on_broken_groups() {
+        assert_eq!(
+            format("fx::fade_to(Color::Red, Color::Blue, (500, QuadOut,),)"),
+            "fx::fade_to(Color::Red, Color::Blue, (500, QuadOut))\n",
+        );
+        assert_eq!(format("fx::fade_to(\n    Color::Red,\n    Color::Blue,\n    500\n)"), "fx::fade_to(Color::Red, Color::Blue, 500)\n");
+
+        let long = format!("fx::fade_to(Color::Red, Color::Blue, ({}, QuadOut))", "1".repeat(MAX_WIDTH));
+        assert!(format(&long).ends_with(",\n)\n"), "{}", format(&long));
+    }
+
+    #[test]
+    fn nests_parallel_and_sequence() {
+        assert_eq!(
+            format("fx::parallel(&[fx::sequence(&[fx::dissolve(500), fx::coalesce(500)]), fx::fade_to_fg(Color::Red, 500)])"),
+            "\
+fx::parallel(&[
+    fx::sequence(&[
+        fx::dissolve(500),
+        fx::coalesce(500),
+    ]),
+    fx::fade_to_fg(Color::Red, 500),
+])
+",
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use std::sync::mpsc;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use tachyonfx::Duration;
+use crate::app::App;
+use crate::canvas::{parse_canvas, CanvasError};
+use crate::canvas::layout::render_layout;
+use crate::clock::ManualClock;
+use crate::diagnostic::{compile_dsl, Diagnostic};
+use crate::event::AppEvent;
+use crate::playback::FRAME_DURATION;
+
+/// A rendered frame and the time it was rendered at.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub timestamp: Duration,
+    pub buffer: Buffer,
+}
+
+/// The canvas failed to parse or the effect DSL failed to compile.
+#[derive(Debug)]
+pub enum LoadError {
+    Canvas(CanvasError),
+    Dsl(Vec<Diagnostic>),
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Canvas(e)        => write!(f, "{e}"),
+            LoadError::Dsl(diagnostics) => {
+                for (i, diagnostic) in diagnostics.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{diagnostic}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// Drives an [App] without a terminal. Time advances by a fixed step per frame,
+/// so the rendered frames are the same on every run; except for effects drawing
+/// random numbers, as tachyonfx seeds them from the system clock.
+pub struct Headless {
+    app: App,
+    clock: ManualClock,
+    events: mpsc::Receiver<AppEvent>,
+    frame_duration: Duration,
+    frame: Buffer,
+    frame_count: usize,
+}
+
+impl Headless {
+    /// Loads the canvas and compiles the effect; fails if either is invalid.
+    pub fn new(canvas: &str, dsl: &str) -> Result<Self, LoadError> {
+        // checked up front, as the app only reports errors through the popup
+        parse_canvas(canvas).map_err(LoadError::Canvas)?;
+        Self::load(AppEvent::UpdateCanvas(canvas.to_string()), dsl)
+    }
+
+    /// Renders the widget layout as the canvas and compiles the effect; see
+    /// [canvas::layout](crate::canvas::layout).
+    pub fn with_layout(layout: &str, dsl: &str) -> Result<Self, LoadError> {
+        render_layout(layout).map_err(LoadError::Canvas)?;
+        Self::load(AppEvent::UpdateCanvasLayout(layout.to_string()), dsl)
+    }
+
+    fn load(canvas: AppEvent, dsl: &str) -> Result<Self, LoadError> {
+        compile_dsl(dsl).map_err(LoadError::Dsl)?;
+
+        let (sender, events) = mpsc::channel();
+        let clock = ManualClock::default();
+        let mut app = App::new(sender);
+        app.set_clock(clock.clone());
+        app.apply_event(canvas);
+        app.apply_event(AppEvent::CompileDsl(dsl.to_string()));
+
+        let size = app.canvas_size();
+        Ok(Self {
+            app,
+            clock,
+            events,
+            frame_duration: FRAME_DURATION,
+            frame: Buffer::empty(Rect::new(0, 0, size.width, size.height)),
+            frame_count: 0,
+        })
+    }
+
+    /// time between two frames; defaults to [FRAME_DURATION]
+    pub fn frame_duration(mut self, frame_duration: Duration) -> Self {
+        self.frame_duration = frame_duration;
+        self
+    }
+
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    /// applies an event to the app, e.g. to change the playback speed
+    pub fn dispatch(&mut self, event: AppEvent) {
+        self.app.apply_event(event);
+    }
+
+    /// duration of a single run of the effect
+    pub fn effect_duration(&self) -> Option<Duration> {
+        self.app.playback().duration()
+    }
+
+    /// number of frames needed to play the effect from start to end, including
+    /// the first and the last frame
+    pub fn frames_for(&self, duration: Duration) -> usize {
+        let step = self.frame_duration.as_millis().max(1);
+        duration.as_millis().div_ceil(step) as usize + 1
+    }
+
+    /// timestamp of the next frame
+    pub fn timestamp(&self) -> Duration {
+        Duration::from_millis(self.frame_count as u32 * self.frame_duration.as_millis())
+    }
+
+    /// Renders the next frame. The first frame is rendered at t=0; each following
+    /// frame advances time by the frame duration.
+    pub fn next_frame(&mut self) -> &Buffer {
+        while let Ok(event) = self.events.try_recv() {
+            self.app.apply_event(event);
+        }
+
+        let delta = match self.frame_count {
+            0 => Duration::ZERO,
+            _ => self.frame_duration,
+        };
+
+        self.clock.advance(delta);
+        self.app.update_time();
+        self.frame.reset();
+        self.app.render_canvas(&mut self.frame);
+        self.frame_count += 1;
+
+        &self.frame
+    }
+
+    /// renders the frames covering `duration`, starting with the next frame
+    pub fn record(&mut self, duration: Duration) -> Vec<Frame> {
+        (0..self.frames_for(duration))
+            .map(|_| {
+                let timestamp = self.timestamp();
+                let buffer = self.next_frame().clone();
+                Frame { timestamp, buffer }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+    use super::*;
+
+    // fades leave cells without a color untouched
+    const CANVAS: &str = "\x1b[38;2;0;255;0mhello\nworld\x1b[0m";
+
+    fn symbols(buffer: &Buffer) -> String {
+        buffer.content().iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn records_frames_at_a_fixed_step_until_the_effect_ends() {
+        let mut headless = Headless::new(CANVAS, "fx::fade_to_fg(Color::Rgb(255, 0, 0), 100)")
+            .unwrap()
+            .frame_duration(Duration::from_millis(40));
+
+        let duration = headless.effect_duration().unwrap();
+        assert_eq!(duration, Duration::from_millis(100));
+        assert_eq!(headless.frames_for(duration), 4);
+
+        let frames = headless.record(duration);
+        let timestamps: Vec<_> = frames.iter().map(|f| f.timestamp.as_millis()).collect();
+        assert_eq!(timestamps, [0, 40, 80, 120]);
+        assert_eq!(headless.timestamp(), Duration::from_millis(160));
+
+        let last = &frames[3].buffer;
+        assert_eq!(last.area, Rect::new(0, 0, 5, 2));
+        assert_eq!(symbols(last), "helloworld");
+        assert!(frames[0].buffer.content().iter().all(|cell| cell.fg == Color::Rgb(0, 255, 0)));
+        assert!(last.content().iter().all(|cell| cell.fg == Color::Rgb(255, 0, 0)));
+    }
+
+    #[test]
+    fn frames_are_the_same_on_every_run() {
+        let record = || Headless::new(CANVAS, "fx::fade_to(Color::Rgb(255, 0, 0), Color::Blue, (200, QuadOut))")
+            .unwrap()
+            .record(Duration::from_millis(200));
+
+        let buffers = |frames: Vec<Frame>| frames.into_iter().map(|f| f.buffer).collect::<Vec<_>>();
+        let first = buffers(record());
+        assert_eq!(first, buffers(record()));
+        assert_ne!(first.first(), first.last());
+    }
+
+    #[test]
+    fn fails_to_load_an_invalid_effect() {
+        let Err(LoadError::Dsl(diagnostics)) = Headless::new(CANVAS, "fx::nope(100)") else {
+            panic!("expected the effect to fail to compile");
+        };
+        assert_eq!(diagnostics.len(), 1);
+    }
+}
@@ -0,0 +1,150 @@
+//! Splits effect DSL source into tokens. Unlike the tachyonfx tokenizer, it keeps
+//! comments and never fails: anything it doesn't recognize becomes a
+//! single-character punctuation token.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    Ident,
+    Number,
+    /// string literal, including the quotes; may be unterminated
+    Str,
+    LineComment,
+    /// may be unterminated
+    BlockComment,
+    Open(char),
+    Close(char),
+    Punct(char),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    /// byte offset into the source
+    pub start: usize,
+}
+
+impl Token<'_> {
+    /// byte offset just past the token
+    pub fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+}
+
+pub(crate) fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, c)| c);
+        let mut take_while = |f: &dyn Fn(char) -> bool| {
+            while chars.next_if(|&(_, c)| f(c)).is_some() {}
+            chars.peek().map(|&(i, _)| i).unwrap_or(source.len())
+        };
+
+        let (kind, end) = match c {
+            c if c.is_whitespace() => continue,
+            '/' if next == Some('/') => (TokenKind::LineComment, take_while(&|c| c != '\n')),
+            '/' if next == Some('*') => {
+                let end = source[start + 2..].find("*/")
+                    .map(|i| start + 2 + i + 2)
+                    .unwrap_or(source.len());
+                while chars.next_if(|&(i, _)| i < end).is_some() {}
+                (TokenKind::BlockComment, end)
+            }
+            '"' => {
+                let mut escaped = false;
+                let mut end = source.len();
+                for (i, c) in chars.by_ref() {
+                    match c {
+                        '"' if !escaped => {
+                            end = i + 1;
+                            break;
+                        }
+                        '\\' => escaped = !escaped,
+                        _    => escaped = false,
+                    }
+                }
+                (TokenKind::Str, end)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                (TokenKind::Ident, take_while(&|c| c.is_alphanumeric() || c == '_'))
+            }
+            c if c.is_ascii_digit() => {
+                (TokenKind::Number, take_while(&|c| c.is_alphanumeric() || c == '_' || c == '.'))
+            }
+            '(' | '[' | '{' => (TokenKind::Open(c), start + 1),
+            ')' | ']' | '}' => (TokenKind::Close(c), start + 1),
+            c => (TokenKind::Punct(c), start + c.len_utf8()),
+        };
+
+        tokens.push(Token { kind, text: &source[start..end], start });
+    }
+
+    tokens
+}
+
+/// the closing bracket for an opening one
+pub(crate) fn closing_bracket(open: char) -> char {
+    match open {
+        '(' => ')',
+        '[' => ']',
+        _   => '}',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(source).into_iter().map(|t| (t.kind, t.text)).collect()
+    }
+
+    #[test]
+    fn splits_calls_into_paths_brackets_and_literals() {
+        use TokenKind::*;
+
+        assert_eq!(kinds("fx::fade_to(Color::Rgb(1, 2, 3), (1.5e2, QuadOut))"), [
+            (Ident, "fx"), (Punct(':'), ":"), (Punct(':'), ":"), (Ident, "fade_to"), (Open('('), "("),
+            (Ident, "Color"), (Punct(':'), ":"), (Punct(':'), ":"), (Ident, "Rgb"), (Open('('), "("),
+            (Number, "1"), (Punct(','), ","), (Number, "2"), (Punct(','), ","), (Number, "3"), (Close(')'), ")"),
+            (Punct(','), ","), (Open('('), "("), (Number, "1.5e2"), (Punct(','), ","), (Ident, "QuadOut"),
+            (Close(')'), ")"), (Close(')'), ")"),
+        ]);
+    }
+
+    #[test]
+    fn keeps_comments() {
+        use TokenKind::*;
+
+        assert_eq!(kinds("// line\nlet /* block */ a"), [
+            (LineComment, "// line"), (Ident, "let"), (BlockComment, "/* block */"), (Ident, "a"),
+        ]);
+    }
+
+    #[test]
+    fn strings_keep_their_quotes_and_escapes() {
+        assert_eq!(kinds(r#"a("x \" ) y")"#)[2], (TokenKind::Str, r#""x \" ) y""#));
+    }
+
+    #[test]
+    fn unterminated_strings_and_comments_run_to_the_end() {
+        assert_eq!(kinds("a \"open"), [(TokenKind::Ident, "a"), (TokenKind::Str, "\"open")]);
+        assert_eq!(kinds("a /* open"), [(TokenKind::Ident, "a"), (TokenKind::BlockComment, "/* open")]);
+    }
+
+    #[test]
+    fn offsets_are_in_bytes() {
+        let tokens = tokenize("\"é\" x");
+        assert_eq!(tokens[1].start, 5);
+        assert_eq!(tokens[0].end(), 4);
+    }
+
+    #[test]
+    fn unknown_characters_become_punctuation() {
+        assert_eq!(kinds("a $ ¤"), [
+            (TokenKind::Ident, "a"), (TokenKind::Punct('$'), "$"), (TokenKind::Punct('¤'), "¤"),
+        ]);
+    }
+}
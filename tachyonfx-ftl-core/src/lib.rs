@@ -1,12 +1,23 @@
 // lib.rs
 pub mod app;
+pub mod canvas;
+pub mod catalog;
+pub mod clock;
+pub mod diagnostic;
 pub mod dispatcher;
+pub mod effect_tree;
 pub mod effects;
 pub mod event;
+pub mod export;
+pub mod format;
 pub mod gruvbox;
-mod widgets;
+pub mod headless;
+mod lexer;
+pub mod playback;
+pub mod snapshot;
+pub mod widgets;
 
 // Re-export common items for convenience
-pub use app::App;
+pub use app::{App, TimelinePanel};
 pub use dispatcher::Dispatcher;
 pub use event::{AppEvent, KeyCode, KeyEvent, ModifierKeys};
\ No newline at end of file
@@ -0,0 +1,454 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use tachyonfx::dsl::EffectDsl;
+use tachyonfx::{Duration, Effect, Shader};
+use crate::effect_tree::EffectNode;
+use crate::widgets::Timeline;
+
+/// Fixed time step used when stepping and fast-forwarding effects.
+pub const FRAME_DURATION: Duration = Duration::from_millis(16);
+
+/// Playback speeds cycled through by [Playback::increase_time_scale] and
+/// [Playback::decrease_time_scale].
+pub const TIME_SCALES: [f32; 8] = [0.1, 0.25, 0.5, 0.75, 1.0, 1.5, 2.0, 4.0];
+
+/// Playback speed used by the slow-motion toggle.
+pub const SLOW_MOTION: f32 = 0.25;
+
+/// Owns the compiled editor effect and controls how time advances for it.
+///
+/// The DSL source is kept around and recompiled whenever the effect restarts,
+/// rewinds or loops: a clone of the compiled effect would share the state
+/// effects keep in `RefCount`s and closures with the previous run.
+pub struct Playback {
+    /// source of the running effect
+    dsl: Option<String>,
+    /// never processed; describes the effect for the timeline and duration
+    compiled: Option<Effect>,
+    timeline: Option<Timeline>,
+    /// structure of the effect, following the DSL source, for the inspector;
+    /// built on load and refreshed as the effect runs
+    effect_tree: Option<EffectNode>,
+    effect: Option<Effect>,
+    elapsed: Duration,
+    paused: bool,
+    pending_step: Option<Duration>,
+    time_scale: f32,
+    /// fractional milliseconds carried over between scaled frames
+    scaled_remainder: f32,
+    looping: bool,
+    /// pause between the end of the effect and the start of the next run
+    loop_delay: Duration,
+    /// time spent waiting for the next run
+    loop_idle: Duration,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self {
+            dsl: None,
+            compiled: None,
+            timeline: None,
+            effect_tree: None,
+            effect: None,
+            elapsed: Duration::ZERO,
+            paused: false,
+            pending_step: None,
+            time_scale: 1.0,
+            scaled_remainder: 0.0,
+            looping: false,
+            loop_delay: Duration::ZERO,
+            loop_idle: Duration::ZERO,
+        }
+    }
+}
+
+impl Playback {
+    /// Replaces the current effect, compiled from `dsl`, and starts playing it
+    /// from t=0.
+    pub fn load(&mut self, dsl: String, effect: Effect) {
+        self.timeline = Some(Timeline::new(&effect));
+        self.effect_tree = EffectNode::from_dsl(&dsl);
+        self.compiled = Some(effect);
+        self.dsl = Some(dsl);
+        self.rewind();
+    }
+
+    /// the running effect
+    pub fn effect(&self) -> Option<&Effect> {
+        self.effect.as_ref()
+    }
+
+    pub fn timeline(&self) -> Option<&Timeline> {
+        self.timeline.as_ref()
+    }
+
+    /// the effect tree, with the progress of each effect as of the last frame
+    pub fn effect_tree(&self) -> Option<&EffectNode> {
+        self.effect_tree.as_ref()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// time elapsed since the effect started
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// total duration of the effect; only a single iteration is
+    /// counted for effects repeating forever.
+    pub fn duration(&self) -> Option<Duration> {
+        self.compiled.as_ref()
+            .and_then(Shader::timer)
+            .map(|timer| timer.duration())
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// sets the playback speed; e.g. 0.25 plays the effect at quarter speed
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        if time_scale.is_finite() && time_scale > 0.0 {
+            self.time_scale = time_scale;
+            self.scaled_remainder = 0.0;
+        }
+    }
+
+    /// switches to the next faster preset in [TIME_SCALES]
+    pub fn increase_time_scale(&mut self) {
+        let next = TIME_SCALES.iter()
+            .find(|&&scale| scale > self.time_scale)
+            .unwrap_or(&TIME_SCALES[TIME_SCALES.len() - 1]);
+
+        self.set_time_scale(*next);
+    }
+
+    /// switches to the next slower preset in [TIME_SCALES]
+    pub fn decrease_time_scale(&mut self) {
+        let next = TIME_SCALES.iter()
+            .rev()
+            .find(|&&scale| scale < self.time_scale)
+            .unwrap_or(&TIME_SCALES[0]);
+
+        self.set_time_scale(*next);
+    }
+
+    /// toggles between [SLOW_MOTION] and normal speed
+    pub fn toggle_slow_motion(&mut self) {
+        match self.time_scale < 1.0 {
+            true  => self.set_time_scale(1.0),
+            false => self.set_time_scale(SLOW_MOTION),
+        }
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Enables looping with the given pause between runs, or disables it when
+    /// `delay` is `None`.
+    pub fn set_loop(&mut self, delay: Option<Duration>) {
+        self.looping = delay.is_some();
+        self.loop_delay = delay.unwrap_or(self.loop_delay);
+        self.loop_idle = Duration::ZERO;
+    }
+
+    pub fn toggle_loop(&mut self) {
+        self.looping = !self.looping;
+        self.loop_idle = Duration::ZERO;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.pending_step = None;
+    }
+
+    /// rewinds the effect to t=0
+    pub fn restart(&mut self, canvas: &Buffer) {
+        self.seek(Duration::ZERO, canvas);
+    }
+
+    /// pauses playback and advances the effect by a single frame
+    pub fn step_forward(&mut self) {
+        self.paused = true;
+        self.pending_step = Some(FRAME_DURATION);
+    }
+
+    /// pauses playback and rewinds the effect by a single frame
+    pub fn step_backward(&mut self, canvas: &Buffer) {
+        self.paused = true;
+        let t = self.elapsed
+            .checked_sub(FRAME_DURATION)
+            .unwrap_or_default();
+
+        self.seek(t, canvas);
+    }
+
+    /// Recompiles the effect and fast-forwards it to `t`, rendering each
+    /// intermediate frame over a scratch copy of `canvas`.
+    pub fn seek(&mut self, t: Duration, canvas: &Buffer) {
+        let Some(mut effect) = self.recompile() else { return };
+
+        let mut scratch = canvas.clone();
+        let area = scratch.area;
+
+        let mut elapsed = Duration::ZERO;
+        while elapsed < t && effect.running() {
+            let step = (t - elapsed).min(FRAME_DURATION);
+            scratch.clone_from(canvas);
+            effect.process(step, &mut scratch, area);
+            elapsed += step;
+        }
+
+        self.effect = Some(effect);
+        self.elapsed = t;
+        self.pending_step = None;
+        self.refresh_effect_tree();
+    }
+
+    /// Processes the running effect. `delta` is scaled by the time scale, and
+    /// ignored while paused unless a single frame step is pending.
+    pub fn process(&mut self, delta: Duration, buf: &mut Buffer, area: Rect) {
+        let mut delta = match self.paused {
+            true  => self.pending_step.take().unwrap_or_default(),
+            false => self.scale(delta),
+        };
+
+        if self.looping && self.effect.as_ref().is_some_and(Shader::done) {
+            self.loop_idle += delta;
+            if self.loop_idle < self.loop_delay {
+                return;
+            }
+
+            // the first frame of the next run is rendered below
+            self.rewind();
+            delta = Duration::ZERO;
+        }
+
+        if let Some(effect) = self.effect.as_mut().filter(|fx| fx.running()) {
+            effect.process(delta, buf, area);
+            self.elapsed += delta;
+            self.refresh_effect_tree();
+        }
+    }
+
+    /// resets the running effect to a freshly compiled copy
+    fn rewind(&mut self) {
+        self.effect = self.recompile();
+        self.elapsed = Duration::ZERO;
+        self.pending_step = None;
+        self.loop_idle = Duration::ZERO;
+        self.refresh_effect_tree();
+    }
+
+    fn refresh_effect_tree(&mut self) {
+        if let (Some(tree), Some(effect)) = (self.effect_tree.as_mut(), self.effect.as_ref()) {
+            tree.refresh(effect, self.elapsed);
+        }
+    }
+
+    /// a new instance of the effect, sharing no state with earlier runs
+    fn recompile(&self) -> Option<Effect> {
+        let dsl = self.dsl.as_deref()?;
+        EffectDsl::new().compiler().compile(dsl).ok()
+    }
+
+    fn scale(&mut self, delta: Duration) -> Duration {
+        let ms = delta.as_millis() as f32 * self.time_scale + self.scaled_remainder;
+        let whole = ms.floor();
+        self.scaled_remainder = ms - whole;
+
+        Duration::from_millis(whole as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::{Color, Style};
+    use super::*;
+
+    fn canvas() -> Buffer {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 5, 1));
+        buf.set_string(0, 0, "hello", Style::new().fg(Color::Rgb(0, 255, 0)));
+        buf
+    }
+
+    fn playback(dsl: &str) -> Playback {
+        let mut playback = Playback::default();
+        let effect = EffectDsl::new().compiler().compile(dsl).unwrap();
+        playback.load(dsl.to_string(), effect);
+        playback
+    }
+
+    /// processes a frame of `delta` over a fresh copy of the canvas, as the app does
+    fn frame(playback: &mut Playback, delta: Duration) -> Buffer {
+        let mut buf = canvas();
+        let area = buf.area;
+        playback.process(delta, &mut buf, area);
+        buf
+    }
+
+    fn ms(ms: u32) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn steps_pause_and_advance_a_single_frame() {
+        let mut playback = playback("fx::fade_to_fg(Color::Rgb(255, 0, 0), 500)");
+        frame(&mut playback, ms(100));
+        assert_eq!(playback.elapsed(), ms(100));
+
+        playback.step_forward();
+        assert!(playback.is_paused());
+        frame(&mut playback, ms(250));
+        assert_eq!(playback.elapsed(), ms(100) + FRAME_DURATION);
+
+        // nothing is pending after the step
+        frame(&mut playback, ms(250));
+        assert_eq!(playback.elapsed(), ms(100) + FRAME_DURATION);
+
+        playback.step_backward(&canvas());
+        playback.step_backward(&canvas());
+        assert_eq!(playback.elapsed(), ms(100) - FRAME_DURATION);
+    }
+
+    #[test]
+    fn time_stands_still_while_paused() {
+        let mut playback = playback("fx::fade_to_fg(Color::Rgb(255, 0, 0), 500)");
+        frame(&mut playback, ms(100));
+
+        playback.toggle_pause();
+        let paused = frame(&mut playback, ms(100));
+        assert_eq!(playback.elapsed(), ms(100));
+        assert_eq!(frame(&mut playback, ms(100)), paused);
+
+        playback.toggle_pause();
+        frame(&mut playback, ms(100));
+        assert_eq!(playback.elapsed(), ms(200));
+    }
+
+    #[test]
+    fn restart_rewinds_to_the_start() {
+        let mut playback = playback("fx::fade_to_fg(Color::Rgb(255, 0, 0), 500)");
+        let first = frame(&mut playback, Duration::ZERO);
+        frame(&mut playback, ms(300));
+
+        playback.restart(&canvas());
+        assert_eq!(playback.elapsed(), Duration::ZERO);
+        assert_eq!(frame(&mut playback, Duration::ZERO), first);
+    }
+
+    #[test]
+    fn speed_presets_stop_at_the_slowest_and_fastest() {
+        let mut playback = Playback::default();
+        (0..TIME_SCALES.len() + 1).for_each(|_| playback.increase_time_scale());
+        assert_eq!(playback.time_scale(), TIME_SCALES[TIME_SCALES.len() - 1]);
+
+        playback.decrease_time_scale();
+        assert_eq!(playback.time_scale(), 2.0);
+
+        (0..TIME_SCALES.len() + 1).for_each(|_| playback.decrease_time_scale());
+        assert_eq!(playback.time_scale(), TIME_SCALES[0]);
+
+        // a custom speed moves to the neighbouring presets
+        playback.set_time_scale(0.3);
+        playback.increase_time_scale();
+        assert_eq!(playback.time_scale(), 0.5);
+    }
+
+    #[test]
+    fn invalid_speeds_are_ignored() {
+        let mut playback = Playback::default();
+        for scale in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            playback.set_time_scale(scale);
+        }
+        assert_eq!(playback.time_scale(), 1.0);
+    }
+
+    #[test]
+    fn slow_motion_accumulates_fractional_frames() {
+        let mut playback = playback("fx::fade_to_fg(Color::Rgb(255, 0, 0), 500)");
+        playback.toggle_slow_motion();
+        assert_eq!(playback.time_scale(), SLOW_MOTION);
+
+        for _ in 0..4 {
+            frame(&mut playback, FRAME_DURATION);
+        }
+        assert_eq!(playback.elapsed(), FRAME_DURATION);
+
+        // 2.5ms per tick; the remainder carries over to the next tick
+        let elapsed: Vec<_> = (0..4)
+            .map(|_| {
+                frame(&mut playback, ms(10));
+                playback.elapsed().as_millis() - FRAME_DURATION.as_millis()
+            })
+            .collect();
+        assert_eq!(elapsed, [2, 5, 7, 10]);
+
+        playback.toggle_slow_motion();
+        assert_eq!(playback.time_scale(), 1.0);
+    }
+
+    #[test]
+    fn seeking_renders_the_same_frame_as_playing_to_it() {
+        let dsl = "fx::sequence(&[fx::fade_to_fg(Color::Rgb(255, 0, 0), (200, QuadOut)), fx::fade_to_fg(Color::Blue, 200)])";
+        let mut played = playback(dsl);
+        let mut playing = frame(&mut played, Duration::ZERO);
+        for _ in 0..15 {
+            playing = frame(&mut played, FRAME_DURATION);
+        }
+
+        let mut seeked = playback(dsl);
+        frame(&mut seeked, ms(380));
+        seeked.seek(FRAME_DURATION * 15, &canvas());
+        assert_eq!(seeked.elapsed(), played.elapsed());
+        assert_eq!(frame(&mut seeked, Duration::ZERO), playing);
+    }
+
+    #[test]
+    fn seeking_backwards_starts_from_a_fresh_effect() {
+        let mut playback = playback("fx::fade_to_fg(Color::Rgb(255, 0, 0), 200)");
+        let first = frame(&mut playback, Duration::ZERO);
+        frame(&mut playback, ms(300));
+        assert!(playback.effect.as_ref().is_some_and(Shader::done));
+
+        playback.seek(Duration::ZERO, &canvas());
+        assert!(playback.effect.as_ref().is_some_and(Shader::running));
+        assert_eq!(frame(&mut playback, Duration::ZERO), first);
+    }
+
+    #[test]
+    fn loops_restart_after_the_loop_delay() {
+        let mut playback = playback("fx::fade_to_fg(Color::Rgb(255, 0, 0), 100)");
+        playback.set_loop(Some(ms(50)));
+        assert!(playback.is_looping());
+
+        let first = frame(&mut playback, Duration::ZERO);
+        frame(&mut playback, ms(100));
+        assert!(playback.effect.as_ref().is_some_and(Shader::done));
+
+        frame(&mut playback, ms(30));
+        assert!(playback.effect.as_ref().is_some_and(Shader::done));
+        assert_eq!(playback.elapsed(), ms(100));
+
+        assert_eq!(frame(&mut playback, ms(20)), first);
+        assert!(playback.effect.as_ref().is_some_and(Shader::running));
+        assert_eq!(playback.elapsed(), Duration::ZERO);
+    }
+
+    #[test]
+    fn finished_effects_stay_done_without_a_loop() {
+        let mut playback = playback("fx::fade_to_fg(Color::Rgb(255, 0, 0), 100)");
+        playback.set_loop(Some(ms(50)));
+        playback.toggle_loop();
+        assert!(!playback.is_looping());
+
+        frame(&mut playback, ms(100));
+        frame(&mut playback, ms(500));
+        assert!(playback.effect.as_ref().is_some_and(Shader::done));
+        assert_eq!(playback.elapsed(), ms(100));
+    }
+}
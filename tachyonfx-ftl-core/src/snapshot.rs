@@ -0,0 +1,413 @@
+//! Golden snapshots of effects: frames rendered at chosen timestamps are compared
+//! against frames stored from an earlier, accepted run.
+//!
+//! Each case is a directory holding the canvas, the effect and the timestamps to
+//! check; the snapshot is written next to them:
+//!
+//! ```text
+//! dissolve/
+//!   canvas.ansi     ANSI-escaped text
+//!   effect.dsl      effect DSL
+//!   timestamps      milliseconds, separated by whitespace or commas; `#` starts a comment
+//!   snapshot.jsonl  the accepted frames, as written by `export::json`
+//! ```
+//!
+//! Frames are rendered by [Headless] at a fixed step of
+//! [FRAME_DURATION](crate::playback::FRAME_DURATION); each timestamp is captured
+//! by the last frame at or before it.
+//!
+//! Some tachyonfx effects, such as `dissolve`, seed their randomness from the
+//! system clock and render differently on every run. Cases are rendered twice to
+//! tell them apart from regressions; see [Outcome::Unstable].
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use tachyonfx::Duration;
+use crate::export::json::{frame_json, write_json_lines};
+use crate::headless::{Frame, Headless, LoadError};
+
+pub const CANVAS_FILE: &str = "canvas.ansi";
+pub const DSL_FILE: &str = "effect.dsl";
+pub const TIMESTAMPS_FILE: &str = "timestamps";
+pub const SNAPSHOT_FILE: &str = "snapshot.jsonl";
+
+/// Maximum number of cell differences listed per frame.
+const MAX_LISTED_CELLS: usize = 20;
+
+/// A canvas, an effect and the timestamps to snapshot.
+#[derive(Debug, Clone)]
+pub struct SnapshotCase {
+    /// name of the case directory
+    pub name: String,
+    pub dir: PathBuf,
+    pub canvas: String,
+    pub dsl: String,
+    pub timestamps: Vec<Duration>,
+}
+
+/// Result of comparing a case against its snapshot.
+#[derive(Debug)]
+pub enum Outcome {
+    Passed,
+    /// no snapshot has been accepted yet
+    Missing,
+    Failed(Vec<Mismatch>),
+    /// two renders of the case differ, so it can't be compared against a snapshot
+    Unstable,
+}
+
+/// A difference between the rendered frames and the snapshot.
+#[derive(Debug)]
+pub enum Mismatch {
+    FrameCount { expected: usize, actual: usize },
+    Timestamp { expected: Duration, actual: Duration },
+    Size { timestamp: Duration, expected: (u64, u64), actual: (u64, u64) },
+    Cells { timestamp: Duration, cells: Vec<CellDiff> },
+}
+
+/// A cell differing from the snapshot; values as written by `export::json`.
+#[derive(Debug)]
+pub struct CellDiff {
+    pub x: usize,
+    pub y: usize,
+    pub expected: Value,
+    pub actual: Value,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(PathBuf, io::Error),
+    Load(LoadError),
+    /// the snapshot file is not valid JSON Lines
+    Parse(PathBuf, String),
+    /// two renders of the case differ; see [Outcome::Unstable]
+    Unstable(String),
+}
+
+impl SnapshotCase {
+    /// Reads the case in `dir`. The timestamps file is optional and defaults to
+    /// the start of the effect.
+    pub fn load(dir: &Path) -> Result<Self, SnapshotError> {
+        let read = |file: &str| {
+            let path = dir.join(file);
+            std::fs::read_to_string(&path).map_err(|e| SnapshotError::Io(path, e))
+        };
+
+        let timestamps = match dir.join(TIMESTAMPS_FILE).exists() {
+            true  => parse_timestamps(&read(TIMESTAMPS_FILE)?)
+                .map_err(|e| SnapshotError::Parse(dir.join(TIMESTAMPS_FILE), e))?,
+            false => vec![Duration::ZERO],
+        };
+
+        Ok(Self {
+            name: dir.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            dir: dir.to_path_buf(),
+            canvas: read(CANVAS_FILE)?,
+            dsl: read(DSL_FILE)?,
+            timestamps,
+        })
+    }
+
+    /// Loads every case directory below `root`, ordered by name. Directories
+    /// without an effect DSL file are skipped.
+    pub fn discover(root: &Path) -> Result<Vec<Self>, SnapshotError> {
+        let io_error = |e| SnapshotError::Io(root.to_path_buf(), e);
+        let mut dirs = std::fs::read_dir(root)
+            .map_err(io_error)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(io_error)?;
+        dirs.sort();
+
+        dirs.iter()
+            .filter(|dir| dir.join(DSL_FILE).is_file())
+            .map(|dir| Self::load(dir))
+            .collect()
+    }
+
+    pub fn snapshot_path(&self) -> PathBuf {
+        self.dir.join(SNAPSHOT_FILE)
+    }
+
+    /// Renders the frames at the case's timestamps; frames carry the requested
+    /// timestamps rather than the time they were rendered at.
+    pub fn render(&self) -> Result<Vec<Frame>, LoadError> {
+        let mut headless = Headless::new(&self.canvas, &self.dsl)?;
+        let last = self.timestamps.iter().max().copied().unwrap_or_default();
+        let frames = headless.record(last);
+
+        let frames = self.timestamps.iter()
+            .map(|&timestamp| {
+                let frame = frames.iter()
+                    .rev()
+                    .find(|f| f.timestamp <= timestamp)
+                    .unwrap_or(&frames[0]);
+                Frame { timestamp, buffer: frame.buffer.clone() }
+            })
+            .collect();
+
+        Ok(frames)
+    }
+
+    /// compares the rendered frames against the stored snapshot
+    pub fn check(&self) -> Result<Outcome, SnapshotError> {
+        let path = self.snapshot_path();
+        if !path.exists() {
+            return Ok(Outcome::Missing);
+        }
+
+        let stored = std::fs::read_to_string(&path)
+            .map_err(|e| SnapshotError::Io(path.clone(), e))?;
+        let expected = stored.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str::<Value>)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| SnapshotError::Parse(path, e.to_string()))?;
+
+        let frames = self.render().map_err(SnapshotError::Load)?;
+        let actual: Vec<Value> = frames.iter().map(frame_json).collect();
+
+        let mismatches = compare(&expected, &actual);
+        let cells_only = mismatches.iter().all(|m| matches!(m, Mismatch::Cells { .. }));
+        match mismatches.is_empty() {
+            true                                             => Ok(Outcome::Passed),
+            false if cells_only && !self.is_stable(&frames)? => Ok(Outcome::Unstable),
+            false                                            => Ok(Outcome::Failed(mismatches)),
+        }
+    }
+
+    /// Renders the frames and stores them as the new snapshot. Fails without
+    /// writing anything if the case is unstable.
+    pub fn accept(&self) -> Result<(), SnapshotError> {
+        let frames = self.render().map_err(SnapshotError::Load)?;
+        if !self.is_stable(&frames)? {
+            return Err(SnapshotError::Unstable(self.name.clone()));
+        }
+
+        let path = self.snapshot_path();
+
+        let mut out = Vec::new();
+        write_json_lines(&frames, &mut out)
+            .and_then(|_| std::fs::write(&path, out))
+            .map_err(|e| SnapshotError::Io(path, e))
+    }
+
+    /// whether rendering the case again reproduces `frames`
+    fn is_stable(&self, frames: &[Frame]) -> Result<bool, SnapshotError> {
+        let again = self.render().map_err(SnapshotError::Load)?;
+        Ok(frames.iter().zip(&again).all(|(a, b)| a.buffer == b.buffer))
+    }
+}
+
+fn parse_timestamps(s: &str) -> Result<Vec<Duration>, String> {
+    s.lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+        .filter(|t| !t.is_empty())
+        .map(|t| t.parse()
+            .map(Duration::from_millis)
+            .map_err(|_| format!("invalid timestamp '{t}'; expected milliseconds")))
+        .collect()
+}
+
+fn compare(expected: &[Value], actual: &[Value]) -> Vec<Mismatch> {
+    let mut mismatches = Vec::new();
+    if expected.len() != actual.len() {
+        mismatches.push(Mismatch::FrameCount { expected: expected.len(), actual: actual.len() });
+    }
+
+    for (expected, actual) in expected.iter().zip(actual) {
+        let timestamp = |frame: &Value| Duration::from_millis(frame["timestamp"].as_u64().unwrap_or(0) as u32);
+        let size = |frame: &Value| (frame["width"].as_u64().unwrap_or(0), frame["height"].as_u64().unwrap_or(0));
+        let t = timestamp(actual);
+
+        if timestamp(expected) != t {
+            mismatches.push(Mismatch::Timestamp { expected: timestamp(expected), actual: t });
+        } else if size(expected) != size(actual) {
+            mismatches.push(Mismatch::Size { timestamp: t, expected: size(expected), actual: size(actual) });
+        } else {
+            let cells = diff_cells(&expected["cells"], &actual["cells"]);
+            if !cells.is_empty() {
+                mismatches.push(Mismatch::Cells { timestamp: t, cells });
+            }
+        }
+    }
+
+    mismatches
+}
+
+fn diff_cells(expected: &Value, actual: &Value) -> Vec<CellDiff> {
+    let rows = |v: &Value| v.as_array().cloned().unwrap_or_default();
+    let cells = |row: &Value| row.as_array().cloned().unwrap_or_default();
+
+    rows(expected).iter()
+        .zip(rows(actual).iter())
+        .enumerate()
+        .flat_map(|(y, (expected, actual))| {
+            cells(expected).into_iter()
+                .zip(cells(actual))
+                .enumerate()
+                .filter(|(_, (e, a))| e != a)
+                .map(move |(x, (expected, actual))| CellDiff { x, y, expected, actual })
+        })
+        .collect()
+}
+
+impl fmt::Display for CellDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let changes = ["symbol", "fg", "bg", "modifiers"].iter()
+            .filter(|key| self.expected[**key] != self.actual[**key])
+            .map(|key| format!("{key} {} -> {}", self.expected[*key], self.actual[*key]))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        write!(f, "({}, {}) {changes}", self.x, self.y)
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::FrameCount { expected, actual } => {
+                write!(f, "expected {expected} frames, rendered {actual}")
+            }
+            Mismatch::Timestamp { expected, actual } => {
+                write!(f, "expected a frame at {}ms, rendered {}ms", expected.as_millis(), actual.as_millis())
+            }
+            Mismatch::Size { timestamp, expected, actual } => write!(f,
+                "{}ms: expected {}x{} cells, rendered {}x{}",
+                timestamp.as_millis(), expected.0, expected.1, actual.0, actual.1,
+            ),
+            Mismatch::Cells { timestamp, cells } => {
+                write!(f, "{}ms: {} cells differ", timestamp.as_millis(), cells.len())?;
+                for cell in cells.iter().take(MAX_LISTED_CELLS) {
+                    write!(f, "\n  {cell}")?;
+                }
+                if cells.len() > MAX_LISTED_CELLS {
+                    write!(f, "\n  ... and {} more", cells.len() - MAX_LISTED_CELLS)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(path, e)    => write!(f, "{}: {e}", path.display()),
+            SnapshotError::Load(e)        => write!(f, "failed to load the case: {e}"),
+            SnapshotError::Parse(path, e) => write!(f, "{}: {e}", path.display()),
+            SnapshotError::Unstable(name) => write!(f, "{name} renders differently on every run"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+
+    const CANVAS: &str = "\x1b[38;2;0;255;0mhello\nworld\x1b[0m";
+
+    /// a case directory under the system temp dir, removed when dropped
+    struct TempCase(PathBuf);
+
+    impl TempCase {
+        fn new(name: &str, dsl: &str, timestamps: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("tfxed-snapshot-{}-{name}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join(CANVAS_FILE), CANVAS).unwrap();
+            std::fs::write(dir.join(DSL_FILE), dsl).unwrap();
+            std::fs::write(dir.join(TIMESTAMPS_FILE), timestamps).unwrap();
+            Self(dir)
+        }
+
+        fn case(&self) -> SnapshotCase {
+            SnapshotCase::load(&self.0).unwrap()
+        }
+    }
+
+    impl Drop for TempCase {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn parses_timestamps_with_commas_and_comments() {
+        let ms = |values: &[u32]| values.iter().map(|&v| Duration::from_millis(v)).collect::<Vec<_>>();
+
+        assert_eq!(parse_timestamps("0, 50\n# halfway\n100 200 # end"), Ok(ms(&[0, 50, 100, 200])));
+        assert_eq!(parse_timestamps(""), Ok(vec![]));
+        assert!(parse_timestamps("0, 1.5").is_err());
+    }
+
+    #[test]
+    fn accepted_snapshots_pass_until_the_effect_changes() {
+        let dir = TempCase::new("accept", "fx::fade_to_fg(Color::Rgb(255, 0, 0), 100)", "0, 50 # mid\n100");
+        let case = dir.case();
+        assert_eq!(case.name, dir.0.file_name().unwrap().to_string_lossy());
+        assert!(matches!(case.check(), Ok(Outcome::Missing)));
+
+        case.accept().unwrap();
+        assert!(matches!(case.check(), Ok(Outcome::Passed)));
+
+        std::fs::write(dir.0.join(DSL_FILE), "fx::fade_to_fg(Color::Rgb(0, 0, 255), 100)").unwrap();
+        let Ok(Outcome::Failed(mismatches)) = dir.case().check() else {
+            panic!("expected the changed effect to fail");
+        };
+        // the first frame is unchanged, before the fade starts
+        assert!(matches!(
+            &mismatches[..],
+            [Mismatch::Cells { cells: a, .. }, Mismatch::Cells { cells: b, .. }] if a.len() == 10 && b.len() == 10
+        ));
+    }
+
+    #[test]
+    fn frames_are_captured_at_the_last_frame_before_each_timestamp() {
+        let dir = TempCase::new("timestamps", "fx::fade_to_fg(Color::Rgb(255, 0, 0), 100)", "20 16 500");
+        let frames = dir.case().render().unwrap();
+
+        let timestamps: Vec<_> = frames.iter().map(|f| f.timestamp.as_millis()).collect();
+        assert_eq!(timestamps, [20, 16, 500]);
+        assert_eq!(frames[0].buffer, frames[1].buffer);
+        assert_ne!(frames[1].buffer, frames[2].buffer);
+    }
+
+    #[test]
+    fn compares_frame_counts_timestamps_and_sizes() {
+        let frame = |timestamp: u32, width: u32| json!({
+            "timestamp": timestamp,
+            "width": width,
+            "height": 1,
+            "cells": [[]],
+        });
+
+        let mismatches = compare(&[frame(0, 1), frame(16, 1), frame(32, 1)], &[frame(0, 2), frame(20, 1)]);
+        assert!(matches!(&mismatches[..], [
+            Mismatch::FrameCount { expected: 3, actual: 2 },
+            Mismatch::Size { expected: (1, 1), actual: (2, 1), .. },
+            Mismatch::Timestamp { .. },
+        ]));
+        assert_eq!(mismatches[2].to_string(), "expected a frame at 16ms, rendered 20ms");
+    }
+
+    #[test]
+    fn lists_the_cells_that_differ() {
+        let cell = |symbol: &str| json!({ "symbol": symbol, "fg": "#ffffff", "bg": null, "modifiers": [] });
+        let cells = diff_cells(
+            &json!([[cell("a"), cell("b")], [cell("c"), cell("d")]]),
+            &json!([[cell("a"), cell("b")], [cell("x"), cell("d")]]),
+        );
+
+        assert_eq!(cells.len(), 1);
+        assert_eq!(cells[0].to_string(), r#"(0, 1) symbol "c" -> "x""#);
+    }
+}
@@ -1,7 +1,15 @@
+use std::ops::Range;
 use ratatui::buffer::Buffer;
-use ratatui::layout::{Offset, Rect, Size};
-use ratatui::style::Style;
-use ratatui::widgets::Widget;
+use ratatui::layout::{Constraint, Offset, Rect, Size};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Cell, Clear, Padding, Paragraph, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Table, Widget};
+use tachyonfx::{Duration, Effect, Shader};
+use tachyonfx::widget::{EffectTimeline, EffectTimelineRects};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use crate::diagnostic::{self, Diagnostic, ExcerptLine};
+use crate::effect_tree::EffectNode;
+use crate::gruvbox::Gruvbox;
 
 pub struct Ruler {
     measured: Size,
@@ -35,27 +43,27 @@ fn draw_rows(
     style: Style,
     buf: &mut Buffer,
 ) {
-    let mut draw_row = |x, y, width, buf: &mut Buffer| {
+    let draw_row = |x, y, width, buf: &mut Buffer| {
         (x..(x + width))
             .enumerate()
             .map(|(i, x)| (i % 10, x))
             .for_each(|(i, x)| {
                 buf[(x, y)]
-                    .set_style(style.clone())
+                    .set_style(style)
                     .set_char(char::from_digit(i as u32, 10).unwrap());
             });
     };
 
-    let mut draw_row_10 = |x, y, width, buf: &mut Buffer| {
+    let draw_row_10 = |x, y, width, buf: &mut Buffer| {
         (x..(x + width))
             .enumerate()
             .filter(|(i, _)| *i > 0)
-            .filter(|(i, x)| i % 10 == 0)
+            .filter(|(i, _)| i % 10 == 0)
             .map(|(i, x)| (i / 10, x))
             .map(|(i, x)| (i % 10, x))
             .for_each(|(i, x)| {
                 buf[(x, y)]
-                    .set_style(style.clone())
+                    .set_style(style)
                     .set_char(char::from_digit(i as u32, 10).unwrap());
             });
     };
@@ -71,27 +79,27 @@ fn draw_cols(
     style: Style,
     buf: &mut Buffer,
 ) {
-    let mut draw_col = |x, y, height, buf: &mut Buffer| {
+    let draw_col = |x, y, height, buf: &mut Buffer| {
         (y..(y + height))
             .enumerate()
             .map(|(i, y)| (i % 10, y))
             .for_each(|(i, y)| {
                 buf[(x, y)]
-                    .set_style(style.clone())
+                    .set_style(style)
                     .set_char(char::from_digit(i as u32, 10).unwrap());
             });
     };
 
-    let mut draw_col_10 = |x, y, height, buf: &mut Buffer| {
+    let draw_col_10 = |x, y, height, buf: &mut Buffer| {
         (y..(y + height))
             .enumerate()
             .filter(|(i, _)| *i > 0)
-            .filter(|(i, x)| i % 10 == 0)
+            .filter(|(i, _)| i % 10 == 0)
             .map(|(i, y)| (i / 10, y))
             .map(|(i, y)| (i % 10, y))
             .for_each(|(i, y)| {
                 buf[(x, y)]
-                    .set_style(style.clone())
+                    .set_style(style)
                     .set_char(char::from_digit(i as u32, 10).unwrap());
             });
     };
@@ -108,7 +116,629 @@ impl Widget for Ruler {
             .offset(Offset { x: area.x as _, y: area.y as _ })
             .clamp(*buf.area());
 
+        // too small to fit the ruler
+        if ruler_area.width < 4 || ruler_area.height < 4 {
+            return;
+        }
+
         draw_rows(ruler_area, self.style, buf);
         draw_cols(ruler_area, self.style, buf);
     }
-}
\ No newline at end of file
+}
+
+/// Renders the effect as a timeline, one bar per effect, with the
+/// `EffectTimeline` widget of tachyonfx and adds a marker for the current
+/// playback position.
+#[derive(Clone)]
+pub struct Timeline {
+    timeline: EffectTimeline,
+    duration: Duration,
+    playhead: Duration,
+}
+
+/// `EffectTimeline` can't be laid out narrower than its legend; it's measured
+/// at this width first.
+const TIMELINE_PROBE_WIDTH: u16 = 1000;
+
+/// narrowest chart the timeline is rendered with; wider than the area is clipped
+const TIMELINE_MIN_CHART_WIDTH: u16 = 10;
+
+impl Timeline {
+    pub fn new(effect: &Effect) -> Self {
+        let timeline = EffectTimeline::builder()
+            .effect(effect)
+            .chart_style(Style::new())
+            .interval_style(Style::new().fg(Gruvbox::dark4()))
+            .build();
+
+        Self {
+            timeline,
+            duration: effect.timer().map(|timer| timer.duration()).unwrap_or_default(),
+            playhead: Duration::ZERO,
+        }
+    }
+
+    pub fn playhead(mut self, playhead: Duration) -> Self {
+        self.playhead = playhead;
+        self
+    }
+
+    /// number of rows needed to render the full timeline
+    pub fn height(&self) -> u16 {
+        self.layout(0).0.height
+    }
+
+    /// The area the timeline is rendered to, at least `width` wide, and its
+    /// layout. `EffectTimeline` lays out its legends relative to the origin, so
+    /// the timeline is rendered to a buffer of its own.
+    fn layout(&self, width: u16) -> (Rect, EffectTimelineRects) {
+        let probe = self.timeline.layout(Rect::new(0, 0, TIMELINE_PROBE_WIDTH, 1));
+        let min_width = probe.legend.width
+            .max(probe.tree.width + probe.cell_filter.width + probe.areas.width + TIMELINE_MIN_CHART_WIDTH);
+
+        let area = Rect::new(0, 0, width.max(min_width), probe.legend.bottom());
+        (area, self.timeline.layout(area))
+    }
+}
+
+impl Widget for Timeline {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = area.intersection(*buf.area());
+        if area.is_empty() {
+            return;
+        }
+
+        let (timeline_area, rects) = self.layout(area.width);
+        let mut timeline_buf = Buffer::empty(timeline_area);
+        self.timeline.render(timeline_area, &mut timeline_buf);
+
+        let chart = rects.chart;
+        let total = self.duration.as_secs_f32().max(0.001);
+        let offset = self.playhead.min(self.duration).as_secs_f32() / total * chart.width as f32;
+        let playhead = chart.x + (offset as u16).min(chart.width.saturating_sub(1));
+
+        (chart.top()..chart.bottom()).for_each(|y| {
+            let cell = &mut timeline_buf[(playhead, y)];
+            if cell.symbol() == " " {
+                cell.set_symbol("│");
+            }
+            cell.set_fg(Gruvbox::red_bright());
+        });
+
+        let axis = rects.time_intervals();
+        let label = format!("▲{}ms", self.playhead.as_millis());
+        let x = playhead.min(axis.right().saturating_sub(label.chars().count() as u16));
+        timeline_buf.set_string(x.max(axis.x), axis.y, label, Style::new().fg(Gruvbox::red_bright()));
+
+        for y in 0..area.height.min(timeline_area.height) {
+            for x in 0..area.width {
+                buf[(area.x + x, area.y + y)] = timeline_buf[(x, y)].clone();
+            }
+        }
+    }
+}
+
+/// tree rows of `node`, with box-drawing guides prepended to the labels
+fn tree_rows<'a>(
+    node: &'a EffectNode,
+    guide: &str,
+    connector: &str,
+    rows: &mut Vec<(String, &'a EffectNode)>,
+) {
+    rows.push((format!("{guide}{connector}{}", node.name), node));
+
+    let child_guide = match connector {
+        "├ " => format!("{guide}│ "),
+        "└ " => format!("{guide}  "),
+        _    => guide.to_string(),
+    };
+
+    let last = node.children.len().saturating_sub(1);
+    node.children.iter().enumerate().for_each(|(i, child)| {
+        let connector = if i == last { "└ " } else { "├ " };
+        tree_rows(child, &child_guide, connector, rows);
+    });
+}
+
+/// Lists every effect of the tree along with the progress of its timer.
+pub struct Inspector<'a> {
+    tree: &'a EffectNode,
+    style: Style,
+}
+
+impl<'a> Inspector<'a> {
+    /// `tree` should be refreshed from the running effect; see
+    /// [EffectNode::refresh].
+    pub fn new(tree: &'a EffectNode) -> Self {
+        Self {
+            tree,
+            style: Style::default(),
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// number of rows needed to render all effects, including the header
+    pub fn height(&self) -> u16 {
+        self.tree.iter().count() as u16 + 1
+    }
+}
+
+impl Widget for Inspector<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut nodes = Vec::new();
+        tree_rows(self.tree, "", "", &mut nodes);
+
+        let label_width = nodes.iter()
+            .map(|(label, _)| label.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let ms = |d: Duration| Cell::from(Text::from(format!("{}ms", d.as_millis())).right_aligned());
+        let rows = nodes.into_iter().map(|(label, node)| {
+            let style = match node.done {
+                true  => Style::new().fg(Gruvbox::dark4()),
+                false => self.style,
+            };
+
+            Row::new([
+                Cell::from(label),
+                Cell::from(node.cell_filter.clone().unwrap_or_else(|| "*".to_string())),
+                Cell::from(node.interpolation.clone().unwrap_or_default()),
+                ms(node.elapsed),
+                ms(node.remaining()),
+                Cell::from(if node.done { "✓" } else { "·" }),
+            ]).style(style)
+        });
+
+        let header = Row::new(["effect", "filter", "interpolation", "elapsed", "remaining", "done"])
+            .style(Style::new().fg(Gruvbox::light1()).add_modifier(Modifier::BOLD));
+
+        let widths = [
+            Constraint::Length(label_width as u16),
+            Constraint::Fill(1),
+            Constraint::Length(13),
+            Constraint::Length(8),
+            Constraint::Length(9),
+            Constraint::Length(4),
+        ];
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .style(self.style);
+        Widget::render(table, area.intersection(*buf.area()), buf);
+    }
+}
+
+/// Shows diagnostics along with the source lines they refer to, with the span of
+/// each marked. Messages are word-wrapped to fit the area; the content scrolls
+/// when it doesn't fit vertically.
+pub struct DiagnosticPopup<'a> {
+    diagnostics: &'a [Diagnostic],
+    source: &'a str,
+}
+
+impl<'a> DiagnosticPopup<'a> {
+    /// `source` is the text the spans of the diagnostics refer to.
+    pub fn new(diagnostics: &'a [Diagnostic], source: &'a str) -> Self {
+        Self { diagnostics, source }
+    }
+
+    /// The popup area, sized to its content up to the size of `area` and placed
+    /// near the top left corner, along with the content wrapped to fit it.
+    fn layout(&self, area: Rect) -> (Rect, Vec<Line<'a>>) {
+        let excerpts: Vec<_> = self.diagnostics.iter()
+            .map(|diagnostic| diagnostic.excerpt(self.source))
+            .collect();
+
+        // one column of padding on each side; the title and a blank row above,
+        // and another blank row below
+        let width = (self.content_width(&excerpts) as u16 + 2).min(area.width);
+        let lines = self.lines(width.saturating_sub(2) as usize, excerpts);
+        let height = (lines.len() as u16).saturating_add(3).min(area.height);
+
+        let popup_area = Rect {
+            x: area.x + 2.min(area.width - width),
+            y: area.y + 2.min(area.height - height),
+            width,
+            height,
+        };
+
+        (popup_area, lines)
+    }
+
+    fn title(&self) -> String {
+        match self.diagnostics {
+            [diagnostic] => diagnostic.span
+                .map(|span| format!("{span} "))
+                .unwrap_or_else(|| "canvas ".to_string()),
+            _ => format!("{} errors ", self.diagnostics.len()),
+        }
+    }
+
+    /// the location prepended to messages; the title holds it when there's
+    /// a single diagnostic
+    fn location(&self, diagnostic: &Diagnostic) -> String {
+        match (self.diagnostics.len(), diagnostic.span) {
+            (2.., Some(span)) => format!("{span} "),
+            _                 => String::new(),
+        }
+    }
+
+    /// width of the widest line, before wrapping
+    fn content_width(&self, excerpts: &[Vec<ExcerptLine>]) -> usize {
+        let text_width = self.diagnostics.iter().zip(excerpts).flat_map(|(diagnostic, excerpt)| {
+            let location = self.location(diagnostic);
+            let gutter = gutter_width(excerpt);
+
+            diagnostic.message.lines().map(move |line| location.width() + line.width())
+                .chain(diagnostic.hints.iter().map(|hint| HINT.width() + hint.width()))
+                .chain(diagnostic.related.iter().map(|r| related_text(&r.span, &r.message).width()))
+                .chain(excerpt.iter().map(move |line| gutter + line.text.width()))
+        }).max().unwrap_or(0);
+
+        text_width.max(self.title().width())
+    }
+
+    /// the content wrapped to `width`; `excerpts` holds the excerpt of each
+    /// diagnostic
+    fn lines(&self, width: usize, excerpts: Vec<Vec<ExcerptLine<'a>>>) -> Vec<Line<'a>> {
+        let message_style = Style::new().fg(Gruvbox::light0()).add_modifier(Modifier::BOLD);
+        let code_style = Style::new().fg(Gruvbox::light0_soft());
+        let marked_style = code_style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+        let caret_style = Style::new().fg(Gruvbox::light0()).add_modifier(Modifier::BOLD);
+
+        let mut lines = Vec::new();
+        for (i, (diagnostic, excerpt)) in self.diagnostics.iter().zip(excerpts).enumerate() {
+            if i > 0 {
+                lines.push(Line::default());
+            }
+
+            let location = self.location(diagnostic);
+            diagnostic.message.lines()
+                .enumerate()
+                .flat_map(|(j, line)| match j {
+                    0 => word_wrap(&format!("{location}{line}"), width),
+                    _ => word_wrap(line, width),
+                })
+                .for_each(|line| lines.push(Line::from(line).style(message_style)));
+
+            for hint in &diagnostic.hints {
+                let indent = " ".repeat(HINT.width());
+                word_wrap(hint, width.saturating_sub(HINT.width()))
+                    .into_iter()
+                    .enumerate()
+                    .for_each(|(j, line)| lines.push(match j {
+                        0 => Line::from(vec![Span::styled(HINT, Style::new().add_modifier(Modifier::BOLD)), Span::from(line)]),
+                        _ => Line::from(format!("{indent}{line}")),
+                    }));
+            }
+
+            diagnostic.related.iter()
+                .flat_map(|r| word_wrap(&related_text(&r.span, &r.message), width))
+                .for_each(|line| lines.push(Line::from(line)));
+
+            if excerpt.is_empty() {
+                continue;
+            }
+
+            lines.push(Line::default());
+            let gutter_width = gutter_width(&excerpt);
+            let digits = gutter_width - GUTTER_CHROME;
+            let offset = excerpt_offset(&excerpt, width.saturating_sub(gutter_width));
+            let clipped = if offset > 0 { "…" } else { "" };
+            for line in excerpt {
+                let indicator = if line.marked.is_some() { ">" } else { " " };
+                let gutter = format!("{indicator:>2} {:>digits$} │ ", line.number);
+                let [before, span, after] = split_excerpt_line(&line, offset);
+
+                if line.marked.is_none() {
+                    lines.push(Line::from(vec![Span::from(gutter), Span::from(clipped), Span::from(before)])
+                        .style(code_style));
+                    continue;
+                }
+
+                let padding = " ".repeat(gutter.width() + clipped.width() + before.width());
+                let caret = "^".repeat(span.width().max(1));
+                lines.push(Line::from(vec![
+                    Span::styled(gutter, code_style),
+                    Span::styled(clipped, code_style),
+                    Span::styled(before, code_style),
+                    Span::styled(span, marked_style),
+                    Span::styled(after, code_style),
+                ]));
+                lines.push(Line::from(vec![
+                    Span::from(padding),
+                    Span::styled(caret, caret_style),
+                ]));
+            }
+        }
+
+        lines
+    }
+}
+
+const HINT: &str = "hint: ";
+
+/// columns around the line number in the gutter of source excerpts
+const GUTTER_CHROME: usize = 6;
+
+fn gutter_width(excerpt: &[ExcerptLine]) -> usize {
+    let digits = excerpt.last().map(|line| line.number.to_string().len()).unwrap_or(0);
+    digits + GUTTER_CHROME
+}
+
+/// Columns left out at the start of each excerpt line, so that the span on the
+/// first marked line fits in `width` behind an ellipsis.
+fn excerpt_offset(excerpt: &[ExcerptLine], width: usize) -> usize {
+    let Some((text, marked)) = excerpt.iter().find_map(|line| Some((line.text, line.marked.clone()?))) else {
+        return 0;
+    };
+
+    let columns = |chars: Range<usize>| text.chars()
+        .take(chars.end)
+        .skip(chars.start)
+        .map(|c| c.width().unwrap_or(0))
+        .sum::<usize>();
+    let start = columns(0..marked.start);
+    let end = start + columns(marked).max(1);
+
+    match end <= width {
+        true  => 0,
+        false => (end + 1 - width).min(start),
+    }
+}
+
+/// The text of `line` without its first `offset` columns, split into the text
+/// before, within and after the marked characters. A wide character cut in
+/// half is replaced by spaces.
+fn split_excerpt_line(line: &ExcerptLine, offset: usize) -> [String; 3] {
+    let marked = line.marked.clone().unwrap_or(usize::MAX..usize::MAX);
+    let mut parts: [String; 3] = Default::default();
+
+    let mut column = 0;
+    for (i, c) in line.text.chars().enumerate() {
+        let char_width = c.width().unwrap_or(0);
+        column += char_width;
+        if column <= offset {
+            continue;
+        }
+
+        let part = match i {
+            i if i < marked.start => &mut parts[0],
+            i if i < marked.end   => &mut parts[1],
+            _                     => &mut parts[2],
+        };
+        match column - char_width < offset {
+            true  => part.push_str(&" ".repeat(column - offset)),
+            false => part.push(c),
+        }
+    }
+
+    parts
+}
+
+fn related_text(span: &diagnostic::Span, message: &str) -> String {
+    format!("{span} {message}")
+}
+
+/// Breaks `text` into lines no wider than `width`, between words where possible;
+/// words wider than a line are split.
+fn word_wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width = 0;
+
+    for word in text.split_whitespace() {
+        let word_width = word.width();
+        if line_width > 0 && line_width + 1 + word_width > width {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0;
+        }
+        if line_width > 0 {
+            line.push(' ');
+            line_width += 1;
+        }
+
+        for c in word.chars() {
+            let char_width = c.width().unwrap_or(0);
+            if line_width > 0 && line_width + char_width > width {
+                lines.push(std::mem::take(&mut line));
+                line_width = 0;
+            }
+            line.push(c);
+            line_width += char_width;
+        }
+    }
+
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+impl StatefulWidget for DiagnosticPopup<'_> {
+    /// lines scrolled past; clamped to the content
+    type State = u16;
+
+    fn render(self, area: Rect, buf: &mut Buffer, scroll: &mut u16) {
+        let area = area.intersection(*buf.area());
+        let (popup_area, lines) = self.layout(area);
+        if popup_area.width < 3 || popup_area.height < 4 {
+            return;
+        }
+
+        let block = Block::new()
+            .title(self.title())
+            .style(Style::new()
+                .fg(Gruvbox::light1())
+                .bg(Gruvbox::red_bright())
+            )
+            .padding(Padding::symmetric(1, 1));
+
+        let inner = block.inner(popup_area);
+        let max_scroll = (lines.len() as u16).saturating_sub(inner.height);
+        *scroll = (*scroll).min(max_scroll);
+
+        Clear.render(popup_area, buf);
+        block.render(popup_area, buf);
+        Paragraph::new(lines)
+            .scroll((*scroll, 0))
+            .render(inner, buf);
+
+        if max_scroll > 0 {
+            let mut state = ScrollbarState::new(max_scroll as usize + 1)
+                .viewport_content_length(inner.height as usize)
+                .position(*scroll as usize);
+
+            let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None)
+                .track_symbol(Some("│"));
+            let scrollbar_area = Rect { y: inner.y, height: inner.height, ..popup_area };
+            StatefulWidget::render(scrollbar, scrollbar_area, buf, &mut state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tachyonfx::dsl::EffectDsl;
+    use super::*;
+
+    /// widths of the timeline when shown below and next to the canvas
+    const BOTTOM_PANEL_WIDTH: u16 = 100;
+    const SIDE_PANEL_WIDTH: u16 = 40;
+
+    fn timeline() -> Timeline {
+        let effect = EffectDsl::new()
+            .compiler()
+            .compile("fx::sequence(&[fx::fade_to_fg(Color::Red, 100), fx::dissolve(300)])")
+            .unwrap();
+        Timeline::new(&effect)
+    }
+
+    /// Renders the timeline at `playhead` into a panel `width` wide, offset from
+    /// the origin of the buffer, and returns the columns of the playhead marker
+    /// and the row of its label, relative to the panel.
+    fn render_playhead(width: u16, playhead: u32) -> (Vec<u16>, String) {
+        let timeline = timeline().playhead(Duration::from_millis(playhead));
+        let area = Rect::new(3, 2, width, timeline.height());
+        let (_, rects) = timeline.layout(width);
+        let mut buf = Buffer::empty(Rect::new(0, 0, area.right() + 5, area.bottom() + 2));
+        timeline.render(area, &mut buf);
+
+        let is_marker = |x: u16| (rects.chart.top()..rects.chart.bottom())
+            .all(|y| buf[(area.x + x, area.y + y)].fg == Gruvbox::red_bright());
+        let columns = (0..width).filter(|&x| is_marker(x)).collect();
+
+        let axis = rects.time_intervals();
+        let label = (0..width)
+            .map(|x| buf[(area.x + x, area.y + axis.y)].symbol())
+            .collect();
+
+        (columns, label)
+    }
+
+    #[test]
+    fn playhead_starts_at_the_left_edge_of_the_chart() {
+        for width in [BOTTOM_PANEL_WIDTH, SIDE_PANEL_WIDTH] {
+            let (_, rects) = timeline().layout(width);
+            let (columns, label) = render_playhead(width, 0);
+
+            assert_eq!(columns, [rects.chart.x], "panel width {width}");
+            let label_x = label.chars().position(|c| c == '▲').unwrap() as u16;
+            assert_eq!(label_x, rects.chart.x);
+            assert!(label.contains("▲0ms"), "{label}");
+        }
+    }
+
+    #[test]
+    fn playhead_stops_at_the_right_edge_of_the_chart() {
+        for width in [BOTTOM_PANEL_WIDTH, SIDE_PANEL_WIDTH] {
+            let (_, rects) = timeline().layout(width);
+            let (columns, label) = render_playhead(width, 400);
+            assert_eq!(columns, [rects.chart.right() - 1], "panel width {width}");
+            assert!(label.ends_with("▲400ms"), "{label}");
+
+            // past the end of the effect, the marker stays put
+            assert_eq!(render_playhead(width, 1000).0, columns);
+        }
+    }
+
+    #[test]
+    fn playhead_is_placed_proportionally_to_the_elapsed_time() {
+        let (_, rects) = timeline().layout(BOTTOM_PANEL_WIDTH);
+        let (columns, _) = render_playhead(BOTTOM_PANEL_WIDTH, 100);
+        assert_eq!(columns, [rects.chart.x + rects.chart.width / 4]);
+    }
+
+    fn excerpt_line(text: &str, marked: Option<Range<usize>>) -> ExcerptLine<'_> {
+        ExcerptLine { number: 1, text, marked }
+    }
+
+    #[test]
+    fn excerpts_are_only_offset_when_the_span_does_not_fit() {
+        let unmarked = excerpt_line("fx::dissolve(500)", None);
+        assert_eq!(excerpt_offset(std::slice::from_ref(&unmarked), 5), 0);
+
+        let fits = excerpt_line("fx::dissolve(500)", Some(4..12));
+        assert_eq!(excerpt_offset(&[unmarked, fits], 17), 0);
+
+        // a column is left for the ellipsis
+        let text = format!("{}bad", "a".repeat(50));
+        let at_end = excerpt_line(&text, Some(50..53));
+        assert_eq!(excerpt_offset(std::slice::from_ref(&at_end), 20), 34);
+        assert_eq!(split_excerpt_line(&at_end, 34), ["a".repeat(16).as_str(), "bad", ""]);
+
+        // spans wider than the line keep their start in view
+        let wide = excerpt_line(&text, Some(2..40));
+        assert_eq!(excerpt_offset(&[wide], 10), 2);
+    }
+
+    #[test]
+    fn excerpts_are_offset_by_columns_not_characters() {
+        let line = excerpt_line("é世界bad()", Some(3..6));
+        assert_eq!(excerpt_offset(std::slice::from_ref(&line), 5), 4);
+
+        // the wide character cut in half is padded with a space
+        assert_eq!(split_excerpt_line(&line, 4), [" ", "bad", "()"]);
+        assert_eq!(split_excerpt_line(&line, 0), ["é世界", "bad", "()"]);
+    }
+
+    #[test]
+    fn unmarked_excerpt_lines_are_kept_whole() {
+        let line = excerpt_line("let a = 1;", None);
+        assert_eq!(split_excerpt_line(&line, 4), ["a = 1;", "", ""]);
+
+        // an empty span past the end of the line, marked by a caret
+        let line = excerpt_line("fx::dissolve(", Some(13..14));
+        assert_eq!(split_excerpt_line(&line, 0), ["fx::dissolve(", "", ""]);
+    }
+
+    #[test]
+    fn wraps_between_words() {
+        assert_eq!(word_wrap("the quick  brown fox", 10), ["the quick", "brown fox"]);
+        assert_eq!(word_wrap("the quick brown fox", 100), ["the quick brown fox"]);
+        assert_eq!(word_wrap("", 10), [""]);
+    }
+
+    #[test]
+    fn splits_words_wider_than_a_line() {
+        assert_eq!(word_wrap("a abcdefghij", 4), ["a", "abcd", "efgh", "ij"]);
+        assert_eq!(word_wrap("ab c", 1), ["a", "b", "c"]);
+        assert_eq!(word_wrap("ab c", 0), word_wrap("ab c", 1));
+    }
+
+    #[test]
+    fn wraps_by_display_width() {
+        assert_eq!(word_wrap("世界世界", 3), ["世", "界", "世", "界"]);
+        assert_eq!(word_wrap("é é é", 3), ["é é", "é"]);
+        assert_eq!(word_wrap("世 界", 4), ["世", "界"]);
+    }
+}
@@ -1,6 +1,7 @@
 use std::sync::mpsc;
 use ratzilla::event::KeyEvent as RatzillaKeyEvent;
 use tfxed_core::{AppEvent, KeyCode, KeyEvent, ModifierKeys};
+use tfxed_core::event::ERROR_POPUP_PAGE;
 
 #[derive(Debug)]
 pub struct EventHandler {
@@ -19,15 +20,8 @@ impl EventHandler {
         self.sender.clone()
     }
 
-    pub fn next(&self) -> Result<AppEvent, mpsc::RecvError> {
-        self.receiver.recv()
-    }
-
     pub fn try_next(&self) -> Option<AppEvent> {
-        match self.receiver.try_recv() {
-            Ok(e)  => Some(e),
-            Err(_) => None
-        }
+        self.receiver.try_recv().ok()
     }
 
     /// iterates over all currently available events
@@ -39,6 +33,29 @@ impl EventHandler {
     }
 }
 
+/// key bindings for controlling playback of the editor effect;
+/// all bindings use the alt modifier to stay clear of the DSL editor.
+pub fn key_binding(e: &RatzillaKeyEvent) -> Option<AppEvent> {
+    use ratzilla::event::KeyCode as RzKeyCode;
+
+    if !e.alt || e.ctrl {
+        return None;
+    }
+
+    match e.code {
+        RzKeyCode::Char(' ') => Some(AppEvent::TogglePause),
+        RzKeyCode::Char('r') => Some(AppEvent::Restart),
+        RzKeyCode::Char('.') => Some(AppEvent::StepForward),
+        RzKeyCode::Char(',') => Some(AppEvent::StepBackward),
+        RzKeyCode::Char('x') => Some(AppEvent::DismissErrorPopup),
+        RzKeyCode::Up        => Some(AppEvent::ScrollErrorPopup(-1)),
+        RzKeyCode::Down      => Some(AppEvent::ScrollErrorPopup(1)),
+        RzKeyCode::PageUp    => Some(AppEvent::ScrollErrorPopup(-ERROR_POPUP_PAGE)),
+        RzKeyCode::PageDown  => Some(AppEvent::ScrollErrorPopup(ERROR_POPUP_PAGE)),
+        _                    => None,
+    }
+}
+
 pub fn convert_key_event(
     RatzillaKeyEvent { code, ctrl, alt, shift }: RatzillaKeyEvent
 ) -> KeyEvent {
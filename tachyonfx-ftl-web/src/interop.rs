@@ -1,6 +1,10 @@
 use std::sync::mpsc::Sender;
+use std::sync::OnceLock;
 use wasm_bindgen::prelude::*;
-use tfxed_core::{AppEvent::UpdateCanvas, AppEvent::CompileDsl, Dispatcher, AppEvent};
+use tachyonfx::Duration;
+use tfxed_core::{AppEvent::UpdateCanvas, AppEvent::CompileDsl, AppEvent::SetTimeScale, AppEvent::Seek, AppEvent::SetLoop, AppEvent::SetTimelinePanel, AppEvent::ShowInspector, Dispatcher, AppEvent, TimelinePanel};
+use tfxed_core::canvas::presets::{self, PRESETS};
+use tfxed_core::playback::{Playback, TIME_SCALES};
 
 #[wasm_bindgen]
 pub fn compile_dsl(s: &str) {
@@ -12,10 +16,95 @@ pub fn update_canvas(s: &str) {
     sender().dispatch(UpdateCanvas(s.into()));
 }
 
+/// sets the playback speed of the previewed effect; 1.0 is normal speed
+#[wasm_bindgen]
+pub fn set_time_scale(scale: f32) {
+    sender().dispatch(SetTimeScale(scale));
+}
+
+/// the playback speed presets, slowest first; formatted in Rust, as the `f32`
+/// values gain digits when widened to JavaScript numbers
+#[wasm_bindgen]
+pub fn time_scales() -> Vec<String> {
+    TIME_SCALES.iter().map(f32::to_string).collect()
+}
+
+/// moves the previewed effect to the given timestamp, in milliseconds
+#[wasm_bindgen]
+pub fn seek(ms: u32) {
+    sender().dispatch(Seek(Duration::from_millis(ms)));
+}
+
+/// re-runs the previewed effect whenever it finishes, pausing `delay_ms` between runs
+#[wasm_bindgen]
+pub fn set_loop(enabled: bool, delay_ms: u32) {
+    let delay = enabled.then(|| Duration::from_millis(delay_ms));
+    sender().dispatch(SetLoop(delay));
+}
+
+/// shows the effect timeline; `position` is one of "bottom", "side" or "hidden"
+#[wasm_bindgen]
+pub fn set_timeline_panel(position: &str) {
+    let panel = match position {
+        "bottom" => TimelinePanel::Bottom,
+        "side"   => TimelinePanel::Side,
+        _        => TimelinePanel::Hidden,
+    };
+    sender().dispatch(SetTimelinePanel(panel));
+}
+
+/// shows or hides the effect inspector
+#[wasm_bindgen]
+pub fn show_inspector(show: bool) {
+    sender().dispatch(ShowInspector(show));
+}
+
+/// hides the error popup over the preview
+#[wasm_bindgen]
+pub fn dismiss_error_popup() {
+    sender().dispatch(AppEvent::DismissErrorPopup);
+}
+
+/// names of the built-in canvases
+#[wasm_bindgen]
+pub fn canvas_presets() -> Vec<String> {
+    PRESETS.iter().map(|p| p.name.to_string()).collect()
+}
+
+/// the ANSI-escaped text of a built-in canvas, or undefined for unknown names
+#[wasm_bindgen]
+pub fn canvas_preset(name: &str) -> Option<String> {
+    presets::find(name).map(|p| p.source())
+}
+
+/// the effects, methods and values known to the DSL, as JSON; see
+/// [catalog_json](tfxed_core::catalog::catalog_json)
+#[wasm_bindgen]
+pub fn dsl_catalog() -> String {
+    tfxed_core::catalog::catalog_json()
+}
+
+/// the effect DSL reformatted, or undefined if its brackets don't balance
+#[wasm_bindgen]
+pub fn format_dsl(source: &str) -> Option<String> {
+    tfxed_core::format::format_dsl(source).ok()
+}
+
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = window)]
     fn dsl_error_callback(error_message: &str);
+
+    #[wasm_bindgen(js_namespace = window)]
+    fn playback_callback(elapsed_ms: u32, duration_ms: u32);
+}
+
+/// reports the playhead position to the timeline slider
+pub fn notify_playback(playback: &Playback) {
+    playback_callback(
+        playback.elapsed().as_millis(),
+        playback.duration().unwrap_or_default().as_millis(),
+    );
 }
 
 #[wasm_bindgen]
@@ -24,11 +113,9 @@ pub fn notify_error(error_message: &str) {
 }
 
 fn sender() -> Sender<AppEvent> {
-    unsafe {
-        match SENDER.as_ref() {
-            None    => panic!("No sender in global state"),
-            Some(s) => s.event_sender.clone(),
-        }
+    match SENDER.get() {
+        None    => panic!("No sender in global state"),
+        Some(s) => s.event_sender.clone(),
     }
 }
 
@@ -37,9 +124,7 @@ struct JsSender {
 }
 
 pub fn init_global_state(sender: Sender<AppEvent>) {
-    unsafe {
-        SENDER = Some(JsSender { event_sender: sender });
-    }
+    let _ = SENDER.set(JsSender { event_sender: sender });
 }
 
-static mut SENDER: Option<JsSender> = None;
\ No newline at end of file
+static SENDER: OnceLock<JsSender> = OnceLock::new();
\ No newline at end of file
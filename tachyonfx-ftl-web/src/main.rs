@@ -1,11 +1,8 @@
 mod event_handler;
 mod interop;
 
-use std::error::Error;
-use ratatui::style::Stylize;
-
-use crate::event_handler::{convert_key_event, EventHandler};
-use crate::interop::init_global_state;
+use crate::event_handler::{convert_key_event, key_binding, EventHandler};
+use crate::interop::{init_global_state, notify_playback};
 use console_error_panic_hook::set_once as set_panic_hook;
 use eyre::{eyre, Result, WrapErr};
 use ratatui::Terminal as RatTerminal;
@@ -22,14 +19,17 @@ fn main() -> Result<()> {
     // globally set the sender for the JS interop functions
     init_global_state(sender.clone());
 
-    let mut terminal = terminal()?;
+    let terminal = terminal()?;
     terminal.on_key_event(move |e| {
-        if !e.alt && !e.ctrl {
+        if let Some(event) = key_binding(&e) {
+            sender.dispatch(event);
+        } else if !e.alt && !e.ctrl {
             sender.dispatch(AppEvent::KeyPress(convert_key_event(e)));
         }
     });
 
     let mut app = App::new(events.sender());
+    let mut last_playhead = None;
 
     terminal.draw_web(move |f| {
         events.receive_events(|event| {
@@ -39,6 +39,12 @@ fn main() -> Result<()> {
 
         app.update_time();
         app.render_ui(f);
+
+        let playhead = (app.playback().elapsed(), app.playback().duration());
+        if last_playhead != Some(playhead) {
+            notify_playback(app.playback());
+            last_playhead = Some(playhead);
+        }
     });
 
     Ok(())